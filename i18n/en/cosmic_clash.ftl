@@ -1,4 +1,9 @@
 home = Home
+proxies = Proxies
+connections = Connections
+rules = Rules
+providers = Providers
+logs = Logs
 profile = Profile
 settings = Settings
 
@@ -15,6 +20,23 @@ git-description = Git commit {$hash} on {$date}
 vpn-status = VPN Status
 vpn-running = Running
 vpn-stopped = Stopped
+vpn-restarting = Crashed, restarting (attempt {$attempt})
+vpn-crashed = Crashed
+vpn-starting = Starting clash core…
+vpn-starting-description = Waiting for the controller to respond.
+vpn-failed = Clash core failed to start
+vpn-stopping = Stopping clash core…
+vpn-stopping-description = Waiting for the core to shut down.
+core-spawn-failed = Could not launch the core: {$reason}
+core-exited-code = The core exited with code {$code} before it was ready.
+core-exited = The core exited before it was ready.
+core-ready-timeout = The controller did not respond within {$secs} seconds.
+api-unreachable = The clash core is not reachable.
+api-timeout = The clash core did not answer in time.
+api-unauthorized = Wrong API secret.
+api-rejected = The core rejected the request ({$status}): {$message}
+api-decode = Unexpected answer from the core: {$details}
+api-request = Request to the core failed: {$details}
 start-vpn = Start VPN
 stop-vpn = Stop VPN
 no-vpn-running = No VPN connection
@@ -22,6 +44,110 @@ no-vpn-running-description = Press the button below to start the proxy service.
 statistics = Statistics
 upload = Upload
 download = Download
+session-total = Session Total
+session-total-value = ↑ {$up} ↓ {$down}
+active-connections = Active Connections
+core-memory = Core Memory
+traffic-peak = Peak {$rate}
+window-1m = 1 min
+window-10m = 10 min
+window-1h = 1 h
+core-output = Core output
+existing-core-found = A clash core is already running
+existing-core-pid-version = Process {$pid} answers on port {$port} with version {$version}.
+existing-core-version = Port {$port} is in use by a core this app did not start (version {$version}). It cannot be stopped from here; move to another port or stop it yourself.
+existing-core-pid = Process {$pid} from a previous session is still alive, but its controller does not answer.
+existing-core-unauthorized = A controller answers on port {$port} but rejects the API secret.
+adopt-core = Adopt
+kill-core = Stop It
+change-port = Use Another Port
+change-secret = Change API Secret
+run-mode = Mode
+mode-rule = Rule
+mode-global = Global
+mode-direct = Direct
+
+# Proxies page
+refresh = Refresh
+proxies-core-stopped = Start the VPN to see its proxy groups.
+no-proxy-groups = The active profile has no proxy groups.
+proxy-group-summary = {$kind} · {$current} · {$count} members
+show = Show
+hide = Hide
+test-latency = Test
+test-all = Test All
+delay-ms = {$ms} ms
+delay-failed = Timeout
+delay-testing = Testing…
+delay-untested = —
+
+# Connections page
+close-all = Close All
+connections-core-stopped = Start the VPN to see its connections.
+connections-loading = Loading connections…
+connections-shown = {$shown} of {$total} connections
+connections-totals = Total ↑ {$up} · ↓ {$down}
+filter-connections = Filter by host, process, rule or proxy
+destination = Destination
+process = Process
+rule = Rule
+chain = Chain
+started = Started
+
+# Rules page
+rules-core-stopped = Start the VPN to see the rules of the running profile.
+rules-loading = Loading rules…
+search-rules = Search by type, payload or policy
+all-policies = All Policies
+rules-shown = {$matching} of {$total} rules
+rules-shown-limited = First {$shown} of {$matching} matching rules ({$total} in total)
+
+# Providers page
+providers-core-stopped = Start the VPN to manage the profile's providers.
+providers-loading = Loading providers…
+no-providers = The active profile has no proxy or rule providers.
+proxy-providers = Proxy Providers
+rule-providers = Rule Providers
+rule-providers-unsupported = This core does not report rule providers.
+proxy-provider-summary = {$vehicle} · {$alive}/{$count} nodes alive · updated {$updated}
+rule-provider-summary = {$vehicle} · {$behavior} · {$count} rules · updated {$updated}
+health-check = Health Check
+update = Update
+updating = Updating…
+never = never
+subscription-usage = {$used} of {$total} used
+subscription-usage-unlimited = {$used} used
+subscription-expires = {$quota} · expires {$date}
+
+# Logs page
+clear = Clear
+export = Export
+pause = Pause
+resume = Resume
+filter-logs = Filter log lines
+log-debug = Debug
+log-info = Info
+log-warning = Warning
+log-error = Error
+log-silent = Silent
+logs-core-stopped = Start the VPN to follow the core log.
+logs-following = Following the log, {$lines} lines buffered
+logs-paused = Paused, {$lines} lines shown, {$held} new lines held back
+logs-exported = Saved to {$path}
+logs-export-failed = Export failed: {$reason}
+
+# Usage page
+usage = Usage
+no-usage = No traffic recorded yet. Usage is counted while the VPN is running.
+usage-this-month = This Month
+usage-today = Today
+usage-month-split = This month
+usage-split = ↑ {$up}  ↓ {$down}
+usage-of-quota = {$used} of {$quota} used ({$percent}%)
+quota-nearly-used = Most of this month's quota is used.
+quota-exceeded = This month's quota is used up.
+usage-by-profile = This Month by Profile
+usage-by-day = Last 30 Days
 
 # Profile page
 clash-version = Clash Version
@@ -35,13 +161,85 @@ reload = Reload
 reload-config = Reload Config
 select = Select
 selected = Selected
+profile-rejected = The core rejected {$profile}
+config-invalid = Invalid config: {$message}
+config-invalid-line = Invalid config at line {$line}: {$message}
+config-invalid-file-line = Invalid config at line {$line} of {$file}, the profile merged with the override: {$message}
+add-subscription = Add Subscription
+add = Add
+profile-name = Name
+profile-name-placeholder = Saved as <name>.yaml in the config directory
+subscription-url = Subscription URL
+subscription-url-placeholder = https://…
+user-agent = User-Agent
+refresh-interval = Refresh Interval
+refresh-interval-placeholder = Hours, 0 to refresh by hand
+profile-name-taken = A profile named {$name} already exists
+subscription-url-invalid = The URL must start with http:// or https://
+refresh-hours-invalid = The refresh interval must be a whole number of hours
+profile-updated-at = Updated {$date}
+profile-not-downloaded = Not downloaded yet
+remote-profile-status = {$updated}
+remote-profile-status-auto = {$updated}, refreshed every {$hours} h
+profile-download-failed = Download failed: {$reason}
+subscription-nearly-used = {$percent}% of the subscription's traffic is used
+subscription-used-up = The subscription's traffic is used up
+subscription-expires-soon = The subscription expires in {$days} days
+subscription-expired = The subscription has expired
+local-profile-status = {$file} · {$updated}
+profile-last-used = Last used {$date}
+profile-notes-placeholder = Notes
+profile-copy-name = {$name} (copy)
+delete = Delete
+delete-profile-confirm = Delete this profile and its file?
+profile-duplicate-failed = Could not duplicate the profile: {$reason}
+profile-delete-failed = Could not delete the profile: {$reason}
+profile-delete-active = The selected profile cannot be deleted; select another one first
+profile-override = Override
+override-description = Merged onto the selected profile each time the core starts or reloads. External controller and secret always come from the settings.
+override-file = Override file
+edit-override = Edit
+override-open-failed = Could not open the override: {$reason}
+show-merged-config = Show Running Config
+hide-merged-config = Hide Running Config
+merged-config-failed = Could not read the running config: {$reason}
+merged-config-missing = No config has been generated yet; it is written when the core starts or reloads
 
 # Settings page
 application-settings = Application
 api-settings = API
+core-management = Core Management
+manifest-url = Release Manifest
+core-updates = Managed Core
+core-update-idle = Download cores from the release manifest.
+core-update-checking = Checking for updates…
+core-update-latest = The installed core is up to date.
+core-update-available = Version {$version} is available.
+core-update-installing = Installing {$version}…
+core-update-installed = Installed {$version}.
+core-update-failed = Update failed: {$reason}
+check-for-updates = Check for Updates
+install = Install
+rollback = Roll Back
+latency-test = Latency Test
+delay-test-url = Test URL
+delay-timeout = Timeout
+traffic-usage = Traffic Usage
+monthly-quota = Monthly Quota
+gibibytes = {$gib} GiB
+milliseconds = {$ms} ms
 clash-binary = Clash Binary
 auto-detect = Auto-detect
+clash-core = Core
+core-detecting = Detecting…
+core-detected = {$flavour} {$version}
+core-too-old = {$flavour} {$version} is older than the supported {$minimum}
+core-not-recognized = Not a usable clash core: {$reason}
+unknown = Unknown
 config-directory = Config Directory
+max-restarts = Max Restarts
+stop-timeout = Stop Timeout
+seconds = {$secs} s
 default = Default
 api-port = API Port
 api-secret = API Secret
@@ -54,3 +252,9 @@ binary-path-placeholder = Path to clash binary
 config-dir-placeholder = Path to config directory
 api-port-placeholder = e.g. 9090
 api-secret-placeholder = Optional secret
+max-restarts-placeholder = e.g. 5
+stop-timeout-placeholder = Seconds before the core is killed
+manifest-url-placeholder = URL of the JSON release index
+delay-test-url-placeholder = URL fetched through each proxy
+delay-timeout-placeholder = Milliseconds, e.g. 5000
+monthly-quota-placeholder = GiB per month, empty for none
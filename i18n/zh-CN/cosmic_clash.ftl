@@ -1,4 +1,9 @@
 home = 主页
+proxies = 代理
+connections = 连接
+rules = 规则
+providers = 提供者
+logs = 日志
 profile = 配置
 settings = 设置
 
@@ -15,6 +20,23 @@ git-description = Git commit {$hash} on {$date}
 vpn-status = VPN 状态
 vpn-running = 运行中
 vpn-stopped = 已停止
+vpn-restarting = 已崩溃，正在重启（第 {$attempt} 次）
+vpn-crashed = 已崩溃
+vpn-starting = 正在启动 Clash 核心…
+vpn-starting-description = 正在等待控制接口响应。
+vpn-failed = Clash 核心启动失败
+vpn-stopping = 正在停止 Clash 核心…
+vpn-stopping-description = 正在等待核心退出。
+core-spawn-failed = 无法启动核心：{$reason}
+core-exited-code = 核心在就绪前退出，退出码 {$code}。
+core-exited = 核心在就绪前退出。
+core-ready-timeout = 控制接口在 {$secs} 秒内未响应。
+api-unreachable = 无法连接 Clash 核心。
+api-timeout = Clash 核心未及时响应。
+api-unauthorized = API 密钥错误。
+api-rejected = 核心拒绝了请求（{$status}）：{$message}
+api-decode = 核心返回了无法识别的响应：{$details}
+api-request = 请求核心失败：{$details}
 start-vpn = 启动 VPN
 stop-vpn = 停止 VPN
 no-vpn-running = 未连接到 VPN
@@ -22,6 +44,110 @@ no-vpn-running-description = 按下下方按钮启动代理服务。
 statistics = 统计信息
 upload = 上传
 download = 下载
+session-total = 本次会话总量
+session-total-value = ↑ {$up} ↓ {$down}
+active-connections = 活动连接
+core-memory = 核心内存
+traffic-peak = 峰值 {$rate}
+window-1m = 1 分钟
+window-10m = 10 分钟
+window-1h = 1 小时
+core-output = 核心输出
+existing-core-found = 已有 Clash 核心在运行
+existing-core-pid-version = 进程 {$pid} 在端口 {$port} 上响应，版本 {$version}。
+existing-core-version = 端口 {$port} 已被非本应用启动的核心占用（版本 {$version}），无法在此停止；请改用其他端口或自行停止该核心。
+existing-core-pid = 上次会话的进程 {$pid} 仍在运行，但其控制接口无响应。
+existing-core-unauthorized = 端口 {$port} 上的控制接口拒绝了当前的 API 密钥。
+adopt-core = 接管
+kill-core = 结束它
+change-port = 使用其他端口
+change-secret = 修改 API 密钥
+run-mode = 模式
+mode-rule = 规则
+mode-global = 全局
+mode-direct = 直连
+
+# 代理页
+refresh = 刷新
+proxies-core-stopped = 启动 VPN 后即可查看代理组。
+no-proxy-groups = 当前配置没有代理组。
+proxy-group-summary = {$kind} · {$current} · {$count} 个节点
+show = 展开
+hide = 收起
+test-latency = 测速
+test-all = 全部测速
+delay-ms = {$ms} 毫秒
+delay-failed = 超时
+delay-testing = 测速中…
+delay-untested = —
+
+# 连接页
+close-all = 全部关闭
+connections-core-stopped = 启动 VPN 后即可查看连接。
+connections-loading = 正在加载连接…
+connections-shown = 显示 {$shown} / {$total} 个连接
+connections-totals = 累计 ↑ {$up} · ↓ {$down}
+filter-connections = 按主机、进程、规则或代理筛选
+destination = 目标
+process = 进程
+rule = 规则
+chain = 链路
+started = 开始时间
+
+# 规则页
+rules-core-stopped = 启动 VPN 后即可查看当前配置的规则。
+rules-loading = 正在加载规则…
+search-rules = 按类型、内容或策略搜索
+all-policies = 全部策略
+rules-shown = {$matching} / {$total} 条规则
+rules-shown-limited = 匹配 {$matching} 条中的前 {$shown} 条（共 {$total} 条）
+
+# 提供者页
+providers-core-stopped = 启动 VPN 后即可管理配置中的提供者。
+providers-loading = 正在加载提供者…
+no-providers = 当前配置没有代理或规则提供者。
+proxy-providers = 代理提供者
+rule-providers = 规则提供者
+rule-providers-unsupported = 此核心不提供规则提供者信息。
+proxy-provider-summary = {$vehicle} · {$alive}/{$count} 个节点可用 · 更新于 {$updated}
+rule-provider-summary = {$vehicle} · {$behavior} · {$count} 条规则 · 更新于 {$updated}
+health-check = 健康检查
+update = 更新
+updating = 更新中…
+never = 从未
+subscription-usage = 已用 {$used} / {$total}
+subscription-usage-unlimited = 已用 {$used}
+subscription-expires = {$quota} · {$date} 到期
+
+# 日志页
+clear = 清空
+export = 导出
+pause = 暂停
+resume = 继续
+filter-logs = 筛选日志
+log-debug = 调试
+log-info = 信息
+log-warning = 警告
+log-error = 错误
+log-silent = 静默
+logs-core-stopped = 启动 VPN 后即可查看核心日志。
+logs-following = 正在跟踪日志，已缓存 {$lines} 行
+logs-paused = 已暂停，显示 {$lines} 行，另有 {$held} 行新日志待显示
+logs-exported = 已保存到 {$path}
+logs-export-failed = 导出失败：{$reason}
+
+# 用量页
+usage = 用量
+no-usage = 尚无流量记录。VPN 运行期间会统计用量。
+usage-this-month = 本月
+usage-today = 今天
+usage-month-split = 本月
+usage-split = ↑ {$up}  ↓ {$down}
+usage-of-quota = 已用 {$used}，共 {$quota}（{$percent}%）
+quota-nearly-used = 本月流量即将用完。
+quota-exceeded = 本月流量已用完。
+usage-by-profile = 本月各配置用量
+usage-by-day = 最近 30 天
 
 # 配置页
 clash-version = Clash 版本
@@ -35,13 +161,85 @@ reload = 重载
 reload-config = 重载配置
 select = 选择
 selected = 已选
+profile-rejected = 核心拒绝了 {$profile}
+config-invalid = 配置无效：{$message}
+config-invalid-line = 配置第 {$line} 行无效：{$message}
+config-invalid-file-line = 配置无效，位于 {$file}（配置与覆写合并后的结果）第 {$line} 行：{$message}
+add-subscription = 添加订阅
+add = 添加
+profile-name = 名称
+profile-name-placeholder = 以 <名称>.yaml 保存到配置目录
+subscription-url = 订阅地址
+subscription-url-placeholder = https://…
+user-agent = User-Agent
+refresh-interval = 更新间隔
+refresh-interval-placeholder = 小时，0 表示手动更新
+profile-name-taken = 已存在名为 {$name} 的配置
+subscription-url-invalid = 地址必须以 http:// 或 https:// 开头
+refresh-hours-invalid = 更新间隔必须是整数小时
+profile-updated-at = 更新于 {$date}
+profile-not-downloaded = 尚未下载
+remote-profile-status = {$updated}
+remote-profile-status-auto = {$updated}，每 {$hours} 小时更新
+profile-download-failed = 下载失败：{$reason}
+subscription-nearly-used = 订阅流量已使用 {$percent}%
+subscription-used-up = 订阅流量已用完
+subscription-expires-soon = 订阅将在 {$days} 天后到期
+subscription-expired = 订阅已到期
+local-profile-status = {$file} · {$updated}
+profile-last-used = 上次使用于 {$date}
+profile-notes-placeholder = 备注
+profile-copy-name = {$name}（副本）
+delete = 删除
+delete-profile-confirm = 删除此配置及其文件？
+profile-duplicate-failed = 无法复制配置：{$reason}
+profile-delete-failed = 无法删除配置：{$reason}
+profile-delete-active = 无法删除当前选中的配置，请先选择其他配置
+profile-override = 覆写
+override-description = 每次核心启动或重载时合并到所选配置上。外部控制器和密钥始终取自设置。
+override-file = 覆写文件
+edit-override = 编辑
+override-open-failed = 无法打开覆写文件：{$reason}
+show-merged-config = 显示运行中的配置
+hide-merged-config = 隐藏运行中的配置
+merged-config-failed = 无法读取运行中的配置：{$reason}
+merged-config-missing = 尚未生成配置，核心启动或重载时才会写入
 
 # 设置页
 application-settings = 应用程序
 api-settings = 接口
+core-management = 核心管理
+manifest-url = 发布清单
+core-updates = 托管核心
+core-update-idle = 从发布清单下载核心。
+core-update-checking = 正在检查更新…
+core-update-latest = 已安装的核心是最新版本。
+core-update-available = 可用版本 {$version}。
+core-update-installing = 正在安装 {$version}…
+core-update-installed = 已安装 {$version}。
+core-update-failed = 更新失败：{$reason}
+check-for-updates = 检查更新
+install = 安装
+rollback = 回滚
+latency-test = 延迟测试
+delay-test-url = 测试地址
+delay-timeout = 超时时间
+traffic-usage = 流量用量
+monthly-quota = 每月流量
+gibibytes = {$gib} GiB
+milliseconds = {$ms} 毫秒
 clash-binary = Clash 程序
 auto-detect = 自动检测
+clash-core = 核心
+core-detecting = 检测中…
+core-detected = {$flavour} {$version}
+core-too-old = {$flavour} {$version} 低于支持的最低版本 {$minimum}
+core-not-recognized = 不是可用的 Clash 核心：{$reason}
+unknown = 未知
 config-directory = 配置目录
+max-restarts = 最大重启次数
+stop-timeout = 停止超时
+seconds = {$secs} 秒
 default = 默认
 api-port = API 端口
 api-secret = API 密钥
@@ -54,3 +252,9 @@ binary-path-placeholder = Clash 程序路径
 config-dir-placeholder = 配置目录路径
 api-port-placeholder = 例如 9090
 api-secret-placeholder = 可选密钥
+max-restarts-placeholder = 例如 5
+stop-timeout-placeholder = 强制结束核心前等待的秒数
+manifest-url-placeholder = JSON 发布索引的地址
+delay-test-url-placeholder = 通过每个代理访问的地址
+delay-timeout-placeholder = 毫秒，例如 5000
+monthly-quota-placeholder = 每月 GiB，留空表示不限
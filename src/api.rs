@@ -1,17 +1,91 @@
 // SPDX-License-Identifier: AGPL3.0
 
-use std::collections::HashMap;
+use std::{collections::HashMap, time::Duration};
 
+use futures_util::{Stream, StreamExt, TryStreamExt, stream};
 use reqwest;
 use serde::{Deserialize, Serialize};
 
+/// Why a controller call failed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ClashApiError {
+	/// Nothing accepted the connection on the controller address.
+	ConnectionRefused,
+	Timeout,
+	/// The controller rejected our secret (HTTP 401).
+	Unauthorized,
+	/// Any other non-success status, with clash's `message` if it sent one.
+	Status { status: u16, message: String },
+	/// The response body was not what we expected.
+	Decode(String),
+	/// Other transport failures.
+	Request(String),
+}
+
+impl std::fmt::Display for ClashApiError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::ConnectionRefused => write!(f, "controller not reachable"),
+			Self::Timeout => write!(f, "controller timed out"),
+			Self::Unauthorized => write!(f, "wrong API secret"),
+			Self::Status { status, message } => write!(f, "HTTP {}: {}", status, message),
+			Self::Decode(err) => write!(f, "unexpected response: {}", err),
+			Self::Request(err) => write!(f, "request failed: {}", err),
+		}
+	}
+}
+
+impl std::error::Error for ClashApiError {}
+
+impl From<reqwest::Error> for ClashApiError {
+	fn from(err: reqwest::Error) -> Self {
+		if err.is_timeout() {
+			Self::Timeout
+		} else if err.is_connect() {
+			Self::ConnectionRefused
+		} else if err.is_decode() {
+			Self::Decode(err.to_string())
+		} else {
+			Self::Request(err.to_string())
+		}
+	}
+}
+
+impl From<serde_json::Error> for ClashApiError {
+	fn from(err: serde_json::Error) -> Self {
+		Self::Decode(err.to_string())
+	}
+}
+
+pub type ApiResult<T> = Result<T, ClashApiError>;
+
+/// Provider updates and health checks wait for remote servers.
+const PROVIDER_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Error body clash sends with 4xx/5xx responses.
+#[derive(Debug, Deserialize)]
+struct ErrorBody {
+	message: String,
+}
+
 #[derive(Debug, Clone)]
 pub struct ClashApi {
 	client: reqwest::Client,
+	/// Client without a total timeout, for endless endpoints like `/traffic`.
+	stream_client: reqwest::Client,
 	base_url: String,
 	secret: Option<String>,
 }
 
+/// Two clients talk to the same core if they share address and secret; this
+/// is what keeps a streaming subscription alive across updates.
+impl std::hash::Hash for ClashApi {
+	fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+		self.base_url.hash(state);
+		self.secret.hash(state);
+	}
+}
+
 #[derive(Debug, Clone, Deserialize, Default)]
 pub struct Version {
 	pub meta: Option<bool>,
@@ -39,6 +113,393 @@ pub struct ProxiesResponse {
 	pub proxies: HashMap<String, ProxySummary>,
 }
 
+impl ProxySummary {
+	/// Delay of the latest test the core ran itself; 0 means it failed.
+	pub fn last_delay(&self) -> Option<u64> {
+		self.history.as_ref()?.last().map(|record| record.delay)
+	}
+
+	/// Groups list their members in `all`; plain proxies do not.
+	pub fn is_group(&self) -> bool {
+		self.all.is_some()
+	}
+
+	/// Only selector groups accept `PUT /proxies/{group}`.
+	pub fn is_selectable(&self) -> bool {
+		self.proxy_type.as_deref() == Some("Selector")
+	}
+}
+
+impl ProxiesResponse {
+	/// Proxy groups in profile order. Clash reports that order through the
+	/// members of the built-in `GLOBAL` group; anything else goes last, sorted
+	/// by name.
+	pub fn groups(&self) -> Vec<(&str, &ProxySummary)> {
+		let order: Vec<&str> = self
+			.proxies
+			.get("GLOBAL")
+			.and_then(|global| global.all.as_ref())
+			.map(|all| all.iter().map(String::as_str).collect())
+			.unwrap_or_default();
+
+		let mut groups: Vec<(&str, &ProxySummary)> = self
+			.proxies
+			.iter()
+			.filter(|(_, proxy)| proxy.is_group())
+			.map(|(name, proxy)| (name.as_str(), proxy))
+			.collect();
+		groups.sort_by_key(|(name, _)| {
+			let rank = match *name {
+				"GLOBAL" => usize::MAX,
+				name => order.iter().position(|n| *n == name).unwrap_or(usize::MAX - 1),
+			};
+			(rank, name.to_string())
+		});
+		groups
+	}
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct ProxySelectRequest<'a> {
+	name: &'a str,
+}
+
+/// Parameters of a latency test, sent to the core as query arguments.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DelayTest {
+	/// URL the core fetches through each proxy.
+	pub url: String,
+	pub timeout_ms: u32,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct DelayResponse {
+	delay: u64,
+}
+
+/// One `/connections` snapshot.
+#[derive(Debug, Clone, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ConnectionsSnapshot {
+	#[serde(default)]
+	pub download_total: u64,
+	#[serde(default)]
+	pub upload_total: u64,
+	/// Some cores send `null` instead of an empty list.
+	#[serde(default, deserialize_with = "null_as_default")]
+	pub connections: Vec<Connection>,
+}
+
+#[derive(Debug, Clone, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct Connection {
+	pub id: String,
+	#[serde(default)]
+	pub metadata: ConnectionMetadata,
+	#[serde(default)]
+	pub upload: u64,
+	#[serde(default)]
+	pub download: u64,
+	/// RFC 3339 time the connection was opened.
+	#[serde(default)]
+	pub start: String,
+	/// Proxies the connection goes through, outermost group last.
+	#[serde(default, deserialize_with = "null_as_default")]
+	pub chains: Vec<String>,
+	#[serde(default)]
+	pub rule: String,
+	#[serde(default)]
+	pub rule_payload: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ConnectionMetadata {
+	pub network: String,
+	#[serde(rename = "type")]
+	pub conn_type: String,
+	#[serde(rename = "sourceIP")]
+	pub source_ip: String,
+	#[serde(rename = "destinationIP")]
+	pub destination_ip: String,
+	/// mihomo sends ports as strings, clash-rs as numbers.
+	#[serde(deserialize_with = "string_or_number")]
+	pub source_port: String,
+	#[serde(deserialize_with = "string_or_number")]
+	pub destination_port: String,
+	pub host: String,
+	pub process_path: String,
+}
+
+impl Connection {
+	/// Host name if the core sniffed one, else the destination address.
+	pub fn destination(&self) -> String {
+		let meta = &self.metadata;
+		let host = if meta.host.is_empty() {
+			meta.destination_ip.as_str()
+		} else {
+			meta.host.as_str()
+		};
+		if meta.destination_port.is_empty() {
+			host.to_string()
+		} else {
+			format!("{}:{}", host, meta.destination_port)
+		}
+	}
+
+	/// Executable name of the local process, when the core resolved one.
+	pub fn process(&self) -> Option<&str> {
+		let path = self.metadata.process_path.as_str();
+		if path.is_empty() {
+			return None;
+		}
+		path.rsplit(['/', '\\']).next()
+	}
+}
+
+/// Which `/providers/...` family a provider belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ProviderKind {
+	Proxy,
+	Rule,
+}
+
+impl ProviderKind {
+	fn path(self) -> &'static str {
+		match self {
+			Self::Proxy => "/providers/proxies",
+			Self::Rule => "/providers/rules",
+		}
+	}
+}
+
+/// Traffic quota an airport reports for a subscription.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "PascalCase", default)]
+pub struct SubscriptionInfo {
+	pub upload: u64,
+	pub download: u64,
+	pub total: u64,
+	/// Unix time the subscription runs out; 0 if it does not.
+	pub expire: i64,
+}
+
+impl SubscriptionInfo {
+	/// Parses a `subscription-userinfo` header such as
+	/// `upload=455727941; download=6174315083; total=1073741824000; expire=1671815872`.
+	///
+	/// Unknown keys and unparsable values are skipped; `None` means the header
+	/// carried none of the known keys.
+	pub fn from_userinfo_header(value: &str) -> Option<Self> {
+		let mut info = Self::default();
+		let mut found = false;
+		for pair in value.split(';') {
+			let Some((key, value)) = pair.split_once('=') else {
+				continue;
+			};
+			// Some panels send floats or scientific notation
+			let Ok(number) = value.trim().parse::<f64>() else {
+				continue;
+			};
+			let number = number.max(0.0);
+			match key.trim().to_ascii_lowercase().as_str() {
+				"upload" => info.upload = number as u64,
+				"download" => info.download = number as u64,
+				"total" => info.total = number as u64,
+				"expire" => info.expire = number as i64,
+				_ => continue,
+			}
+			found = true;
+		}
+		found.then_some(info)
+	}
+
+	pub fn used(&self) -> u64 {
+		self.upload.saturating_add(self.download)
+	}
+
+	/// Share of the quota used, if there is a quota.
+	pub fn used_ratio(&self) -> Option<f32> {
+		(self.total > 0).then(|| self.used() as f32 / self.total as f32)
+	}
+}
+
+#[derive(Debug, Clone, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ProxyProvider {
+	pub name: String,
+	/// `HTTP`, `File`, or `Compatible` for the implicit provider of inline proxies.
+	#[serde(default)]
+	pub vehicle_type: String,
+	#[serde(default, deserialize_with = "null_as_default")]
+	pub proxies: Vec<ProxySummary>,
+	#[serde(default)]
+	pub updated_at: Option<String>,
+	#[serde(default)]
+	pub subscription_info: Option<SubscriptionInfo>,
+}
+
+impl ProxyProvider {
+	/// Inline proxies show up as a provider too, but cannot be updated.
+	pub fn is_inline(&self) -> bool {
+		self.vehicle_type == "Compatible"
+	}
+}
+
+#[derive(Debug, Clone, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct RuleProvider {
+	pub name: String,
+	#[serde(default)]
+	pub vehicle_type: String,
+	/// `domain`, `ipcidr` or `classical`.
+	#[serde(default)]
+	pub behavior: String,
+	#[serde(default)]
+	pub rule_count: usize,
+	#[serde(default)]
+	pub updated_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct ProvidersResponse<T> {
+	#[serde(default = "HashMap::new")]
+	providers: HashMap<String, T>,
+}
+
+/// One routing rule, in the order the core matches them.
+#[derive(Debug, Clone, Deserialize, Default)]
+pub struct Rule {
+	/// e.g. `DOMAIN-SUFFIX`, `IP-CIDR` or `MATCH`.
+	#[serde(rename = "type")]
+	pub rule_type: String,
+	#[serde(default)]
+	pub payload: String,
+	/// Policy (proxy or group) matching traffic is sent to.
+	#[serde(default)]
+	pub proxy: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Default)]
+struct RulesResponse {
+	#[serde(default, deserialize_with = "null_as_default")]
+	rules: Vec<Rule>,
+}
+
+/// One `/memory` sample.
+#[derive(Debug, Clone, Copy, Deserialize, Default)]
+#[serde(default)]
+pub struct Memory {
+	/// Bytes the core currently uses.
+	pub inuse: u64,
+	/// Memory limit of the core's OS, 0 if unknown.
+	pub oslimit: u64,
+}
+
+/// Verbosity of the `/logs` stream; each level includes the ones above it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+	Debug,
+	#[default]
+	Info,
+	Warning,
+	Error,
+	/// Only valid as the core's own level; the stream would be empty.
+	Silent,
+}
+
+impl LogLevel {
+	/// Levels worth following in the log viewer.
+	pub const ALL: [LogLevel; 4] = [Self::Debug, Self::Info, Self::Warning, Self::Error];
+}
+
+impl std::fmt::Display for LogLevel {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::Debug => write!(f, "debug"),
+			Self::Info => write!(f, "info"),
+			Self::Warning => write!(f, "warning"),
+			Self::Error => write!(f, "error"),
+			Self::Silent => write!(f, "silent"),
+		}
+	}
+}
+
+/// How the core routes traffic.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RunMode {
+	/// Follow the profile's rules.
+	#[serde(alias = "Rule")]
+	Rule,
+	/// Send everything through the `GLOBAL` group.
+	#[serde(alias = "Global")]
+	Global,
+	/// Bypass all proxies.
+	#[serde(alias = "Direct")]
+	Direct,
+}
+
+impl RunMode {
+	pub const ALL: [RunMode; 3] = [Self::Rule, Self::Global, Self::Direct];
+}
+
+/// The parts of the running config we show, from `GET /configs`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Default)]
+#[serde(rename_all = "kebab-case", default)]
+pub struct RuntimeConfig {
+	pub port: u16,
+	pub socks_port: u16,
+	pub mixed_port: u16,
+	pub allow_lan: bool,
+	#[serde(deserialize_with = "unknown_as_none")]
+	pub mode: Option<RunMode>,
+	/// `None` for levels we don't know, such as `trace`.
+	#[serde(deserialize_with = "unknown_as_none")]
+	pub log_level: Option<LogLevel>,
+	pub ipv6: bool,
+	pub tun: Option<TunConfig>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct TunConfig {
+	pub enable: bool,
+}
+
+/// Fields to change on the running core with `PATCH /configs`; unset fields
+/// are left alone.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Default)]
+#[serde(rename_all = "kebab-case")]
+pub struct ConfigPatch {
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub mode: Option<RunMode>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub log_level: Option<LogLevel>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub allow_lan: Option<bool>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub port: Option<u16>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub socks_port: Option<u16>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub mixed_port: Option<u16>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub ipv6: Option<bool>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub tun: Option<TunConfig>,
+}
+
+/// One line of the core's log.
+#[derive(Debug, Clone, Deserialize)]
+pub struct LogEntry {
+	/// Level as sent by the core; kept as text since cores add their own.
+	#[serde(rename = "type")]
+	pub level: String,
+	pub payload: String,
+}
+
 #[derive(Debug, Clone, Deserialize, Default)]
 pub struct Traffic {
 	pub up: u64,
@@ -54,7 +515,11 @@ impl ClashApi {
 	pub fn new(base_url: String, secret: Option<String>) -> Self {
 		Self {
 			client: reqwest::Client::builder()
-				.timeout(std::time::Duration::from_secs(5))
+				.timeout(Duration::from_secs(5))
+				.build()
+				.unwrap_or_default(),
+			stream_client: reqwest::Client::builder()
+				.connect_timeout(Duration::from_secs(5))
 				.build()
 				.unwrap_or_default(),
 			base_url,
@@ -63,44 +528,496 @@ impl ClashApi {
 	}
 
 	fn build_request(&self, method: reqwest::Method, path: &str) -> reqwest::RequestBuilder {
+		self.request_with(&self.client, method, path)
+	}
+
+	fn request_with(&self, client: &reqwest::Client, method: reqwest::Method, path: &str) -> reqwest::RequestBuilder {
 		let url = format!("{}{}", self.base_url, path);
-		let mut req = self.client.request(method, &url);
+		let mut req = client.request(method, &url);
 		if let Some(secret) = &self.secret {
 			req = req.header("Authorization", format!("Bearer {}", secret));
 		}
 		req
 	}
 
-	pub async fn version(&self) -> eyre::Result<Version> {
-		let resp = self.build_request(reqwest::Method::GET, "/version").send().await?;
-		Ok(resp.json().await?)
+	/// Maps non-success statuses to [`ClashApiError`].
+	async fn check(resp: reqwest::Response) -> ApiResult<reqwest::Response> {
+		let status = resp.status();
+		if status.is_success() {
+			return Ok(resp);
+		}
+		if status == reqwest::StatusCode::UNAUTHORIZED {
+			return Err(ClashApiError::Unauthorized);
+		}
+		let text = resp.text().await.unwrap_or_default();
+		let message = serde_json::from_str::<ErrorBody>(&text)
+			.map(|body| body.message)
+			.unwrap_or(text);
+		Err(ClashApiError::Status {
+			status: status.as_u16(),
+			message,
+		})
+	}
+
+	async fn get_json<T: serde::de::DeserializeOwned>(&self, path: &str) -> ApiResult<T> {
+		let resp = self.build_request(reqwest::Method::GET, path).send().await?;
+		let bytes = Self::check(resp).await?.bytes().await?;
+		Ok(serde_json::from_slice(&bytes)?)
+	}
+
+	pub async fn version(&self) -> ApiResult<Version> {
+		self.get_json("/version").await
+	}
+
+	pub async fn proxies(&self) -> ApiResult<ProxiesResponse> {
+		self.get_json("/proxies").await
+	}
+
+	/// Switches selector `group` to its member `name`.
+	pub async fn select_proxy(&self, group: &str, name: &str) -> ApiResult<()> {
+		let path = format!("/proxies/{}", encode_path_segment(group));
+		let resp = self
+			.build_request(reqwest::Method::PUT, &path)
+			.json(&ProxySelectRequest { name })
+			.send()
+			.await?;
+		Self::check(resp).await?;
+		Ok(())
+	}
+
+	/// Measures the latency of proxy `name` in milliseconds.
+	pub async fn proxy_delay(&self, name: &str, test: &DelayTest) -> ApiResult<u64> {
+		let path = format!("/proxies/{}/delay", encode_path_segment(name));
+		let resp: DelayResponse = self.get_delay(&path, test).await?;
+		Ok(resp.delay)
+	}
+
+	/// Tests every member of `group` at once. Members that failed the test are
+	/// missing from the result.
+	pub async fn group_delay(&self, group: &str, test: &DelayTest) -> ApiResult<HashMap<String, u64>> {
+		let path = format!("/group/{}/delay", encode_path_segment(group));
+		self.get_delay(&path, test).await
+	}
+
+	/// Tests `names` one by one with at most `concurrency` requests in flight,
+	/// yielding each result as soon as it is known.
+	pub fn delay_stream(
+		&self,
+		names: Vec<String>,
+		test: DelayTest,
+		concurrency: usize,
+	) -> impl Stream<Item = (String, ApiResult<u64>)> + use<> {
+		let api = self.clone();
+		stream::iter(names)
+			.map(move |name| {
+				let api = api.clone();
+				let test = test.clone();
+				async move {
+					let result = api.proxy_delay(&name, &test).await;
+					(name, result)
+				}
+			})
+			.buffer_unordered(concurrency.max(1))
+	}
+
+	async fn get_delay<T: serde::de::DeserializeOwned>(&self, path: &str, test: &DelayTest) -> ApiResult<T> {
+		// The core answers only after its own timeout, which may exceed ours
+		let timeout = Duration::from_millis(test.timeout_ms.into()) + Duration::from_secs(2);
+		let resp = self
+			.build_request(reqwest::Method::GET, path)
+			.query(&[("url", test.url.as_str()), ("timeout", &test.timeout_ms.to_string())])
+			.timeout(timeout)
+			.send()
+			.await?;
+		let bytes = Self::check(resp).await?.bytes().await?;
+		Ok(serde_json::from_slice(&bytes)?)
+	}
+
+	/// Proxy providers sorted by name.
+	pub async fn proxy_providers(&self) -> ApiResult<Vec<ProxyProvider>> {
+		let resp: ProvidersResponse<ProxyProvider> = self.get_json(ProviderKind::Proxy.path()).await?;
+		let mut providers: Vec<ProxyProvider> = resp.providers.into_values().collect();
+		providers.sort_by(|a, b| a.name.cmp(&b.name));
+		Ok(providers)
+	}
+
+	/// Rule providers sorted by name.
+	pub async fn rule_providers(&self) -> ApiResult<Vec<RuleProvider>> {
+		let resp: ProvidersResponse<RuleProvider> = self.get_json(ProviderKind::Rule.path()).await?;
+		let mut providers: Vec<RuleProvider> = resp.providers.into_values().collect();
+		providers.sort_by(|a, b| a.name.cmp(&b.name));
+		Ok(providers)
 	}
 
-	pub async fn proxies(&self) -> eyre::Result<ProxiesResponse> {
-		let resp = self.build_request(reqwest::Method::GET, "/proxies").send().await?;
-		Ok(resp.json().await?)
+	/// Makes the core fetch provider `name` again.
+	pub async fn update_provider(&self, kind: ProviderKind, name: &str) -> ApiResult<()> {
+		let path = format!("{}/{}", kind.path(), encode_path_segment(name));
+		let resp = self
+			.build_request(reqwest::Method::PUT, &path)
+			.timeout(PROVIDER_TIMEOUT)
+			.send()
+			.await?;
+		Self::check(resp).await?;
+		Ok(())
 	}
 
-	pub async fn reload_config(&self, path: &str) -> eyre::Result<()> {
+	/// Tests every proxy of provider `name`; results land in their history.
+	pub async fn healthcheck_provider(&self, name: &str) -> ApiResult<()> {
+		let path = format!("{}/{}/healthcheck", ProviderKind::Proxy.path(), encode_path_segment(name));
+		let resp = self
+			.build_request(reqwest::Method::GET, &path)
+			.timeout(PROVIDER_TIMEOUT)
+			.send()
+			.await?;
+		Self::check(resp).await?;
+		Ok(())
+	}
+
+	pub async fn rules(&self) -> ApiResult<Vec<Rule>> {
+		let resp: RulesResponse = self.get_json("/rules").await?;
+		Ok(resp.rules)
+	}
+
+	pub async fn connections(&self) -> ApiResult<ConnectionsSnapshot> {
+		self.get_json("/connections").await
+	}
+
+	pub async fn close_connection(&self, id: &str) -> ApiResult<()> {
+		let path = format!("/connections/{}", encode_path_segment(id));
+		let resp = self.build_request(reqwest::Method::DELETE, &path).send().await?;
+		Self::check(resp).await?;
+		Ok(())
+	}
+
+	pub async fn close_all_connections(&self) -> ApiResult<()> {
+		let resp = self.build_request(reqwest::Method::DELETE, "/connections").send().await?;
+		Self::check(resp).await?;
+		Ok(())
+	}
+
+	pub async fn get_configs(&self) -> ApiResult<RuntimeConfig> {
+		self.get_json("/configs").await
+	}
+
+	/// Changes settings of the running core without reloading the profile.
+	pub async fn patch_configs(&self, patch: &ConfigPatch) -> ApiResult<()> {
+		let resp = self
+			.build_request(reqwest::Method::PATCH, "/configs")
+			.json(patch)
+			.send()
+			.await?;
+		Self::check(resp).await?;
+		Ok(())
+	}
+
+	pub async fn reload_config(&self, path: &str) -> ApiResult<()> {
 		let body = ConfigReloadRequest { path };
 		let resp = self
 			.build_request(reqwest::Method::PUT, "/configs")
 			.json(&body)
 			.send()
 			.await?;
-		if !resp.status().is_success() {
-			let text = resp.text().await.unwrap_or_default();
-			return Err(eyre::eyre!("Failed to reload config: {}", text));
-		}
+		Self::check(resp).await?;
 		Ok(())
 	}
 
-	pub async fn traffic(&self) -> eyre::Result<Traffic> {
-		let resp = self.build_request(reqwest::Method::GET, "/traffic").send().await?;
-		let text = resp.text().await?;
-		// Clash traffic endpoint may return SSE stream; extract first data line
-		let json_line = text.lines().find(|l| !l.trim().is_empty()).unwrap_or("{}");
-		let json_line = json_line.strip_prefix("data:").unwrap_or(json_line).trim();
-		Ok(serde_json::from_str(json_line)?)
+	/// Live `/traffic` samples, one per second while the core runs.
+	pub fn traffic_stream(&self) -> impl Stream<Item = ApiResult<Traffic>> + use<> {
+		self.json_stream("/traffic")
+	}
+
+	/// Live `/memory` samples, one per second.
+	pub fn memory_stream(&self) -> impl Stream<Item = ApiResult<Memory>> + use<> {
+		self.json_stream("/memory")
+	}
+
+	/// Live core log at `level` and above.
+	pub fn logs_stream(&self, level: LogLevel) -> impl Stream<Item = ApiResult<LogEntry>> + use<> {
+		self.json_stream(&format!("/logs?level={}", level))
+	}
+
+	/// Reads an endless chunked response as newline-delimited JSON. The stream
+	/// ends when the core closes the connection; callers reconnect.
+	pub fn json_stream<T>(&self, path: &str) -> impl Stream<Item = ApiResult<T>> + use<T>
+	where
+		T: serde::de::DeserializeOwned,
+	{
+		let request = self.request_with(&self.stream_client, reqwest::Method::GET, path);
+		let chunks = stream::once(async move {
+			let resp = Self::check(request.send().await?).await?;
+			Ok::<_, ClashApiError>(resp.bytes_stream().map_err(ClashApiError::from))
+		})
+		.try_flatten();
+
+		chunks
+			.scan(Vec::new(), |buffer: &mut Vec<u8>, chunk| {
+				let items = match chunk {
+					Ok(bytes) => {
+						buffer.extend_from_slice(&bytes);
+						drain_lines(buffer)
+					}
+					Err(err) => vec![Err(err)],
+				};
+				std::future::ready(Some(stream::iter(items)))
+			})
+			.flatten()
+	}
+}
+
+/// Percent-encodes a proxy or group name for use in a URL path; names often
+/// contain spaces, emoji or slashes.
+fn encode_path_segment(segment: &str) -> String {
+	let mut encoded = String::with_capacity(segment.len());
+	for byte in segment.bytes() {
+		match byte {
+			b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => encoded.push(byte as char),
+			_ => encoded.push_str(&format!("%{:02X}", byte)),
+		}
+	}
+	encoded
+}
+
+fn null_as_default<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+	D: serde::Deserializer<'de>,
+	T: Deserialize<'de> + Default,
+{
+	Ok(Option::<T>::deserialize(deserializer)?.unwrap_or_default())
+}
+
+/// Reads a value we may not recognise as `None` instead of failing the whole
+/// response, so a newer core's extra variants don't break decoding.
+fn unknown_as_none<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+	D: serde::Deserializer<'de>,
+	T: serde::de::DeserializeOwned,
+{
+	Ok(serde_json::from_value(serde_json::Value::deserialize(deserializer)?).ok())
+}
+
+fn string_or_number<'de, D>(deserializer: D) -> Result<String, D::Error>
+where
+	D: serde::Deserializer<'de>,
+{
+	Ok(match serde_json::Value::deserialize(deserializer)? {
+		serde_json::Value::String(s) => s,
+		serde_json::Value::Null => String::new(),
+		other => other.to_string(),
+	})
+}
+
+/// Parses every complete line in `buffer`, leaving a trailing partial line.
+fn drain_lines<T: serde::de::DeserializeOwned>(buffer: &mut Vec<u8>) -> Vec<ApiResult<T>> {
+	let mut items = Vec::new();
+	while let Some(end) = buffer.iter().position(|b| *b == b'\n') {
+		let line: Vec<u8> = buffer.drain(..=end).collect();
+		let line = String::from_utf8_lossy(&line);
+		// Some cores speak SSE and prefix each event with `data:`
+		let line = line.trim();
+		let line = line.strip_prefix("data:").unwrap_or(line).trim();
+		if !line.is_empty() {
+			items.push(serde_json::from_str(line).map_err(ClashApiError::from));
+		}
+	}
+	items
+}
+
+#[cfg(test)]
+mod tests {
+	use tokio::{
+		io::{AsyncReadExt, AsyncWriteExt},
+		net::TcpListener,
+	};
+
+	use super::*;
+
+	/// Answers every request on localhost with `status` and `body` and
+	/// returns the base URL.
+	async fn respond(status: &'static str, body: &'static str) -> String {
+		let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+		let addr = listener.local_addr().unwrap();
+		tokio::spawn(async move {
+			while let Ok((mut socket, _)) = listener.accept().await {
+				let mut request = Vec::new();
+				let mut buf = [0; 1024];
+				while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+					match socket.read(&mut buf).await {
+						Ok(0) | Err(_) => break,
+						Ok(n) => request.extend_from_slice(&buf[..n]),
+					}
+				}
+				let response = format!(
+					"HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+					status,
+					body.len(),
+					body
+				);
+				let _ = socket.write_all(response.as_bytes()).await;
+			}
+		});
+		format!("http://{}", addr)
+	}
+
+	#[tokio::test]
+	async fn maps_controller_responses_to_errors() {
+		let api = ClashApi::new(respond("200 OK", r#"{"version":"0.7.1"}"#).await, None);
+		assert_eq!(api.version().await.unwrap().version.as_deref(), Some("0.7.1"));
+
+		let api = ClashApi::new(respond("401 Unauthorized", "").await, Some("wrong".to_string()));
+		assert_eq!(api.version().await.unwrap_err(), ClashApiError::Unauthorized);
+
+		let api = ClashApi::new(respond("400 Bad Request", r#"{"message":"Body invalid"}"#).await, None);
+		assert_eq!(
+			api.version().await.unwrap_err(),
+			ClashApiError::Status {
+				status: 400,
+				message: "Body invalid".to_string(),
+			}
+		);
+
+		// Without clash's JSON error body the raw text is the message
+		let api = ClashApi::new(respond("502 Bad Gateway", "upstream down").await, None);
+		assert_eq!(
+			api.version().await.unwrap_err(),
+			ClashApiError::Status {
+				status: 502,
+				message: "upstream down".to_string(),
+			}
+		);
+
+		let api = ClashApi::new(respond("200 OK", "<html>").await, None);
+		assert!(matches!(api.version().await, Err(ClashApiError::Decode(_))));
+	}
+
+	#[tokio::test]
+	async fn closed_port_is_unreachable() {
+		let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+		let addr = listener.local_addr().unwrap();
+		drop(listener);
+
+		let api = ClashApi::new(format!("http://{}", addr), None);
+		assert_eq!(api.version().await.unwrap_err(), ClashApiError::ConnectionRefused);
+	}
+
+	#[test]
+	fn runtime_config_tolerates_unknown_levels() {
+		let config: RuntimeConfig = serde_json::from_str(r#"{"port":7890,"mode":"rule","log-level":"trace"}"#).unwrap();
+		assert_eq!(config.port, 7890);
+		assert_eq!(config.mode, Some(RunMode::Rule));
+		assert_eq!(config.log_level, None);
+
+		let config: RuntimeConfig = serde_json::from_str(r#"{"mode":"Script","log-level":"warning"}"#).unwrap();
+		assert_eq!(config.mode, None);
+		assert_eq!(config.log_level, Some(LogLevel::Warning));
+	}
+
+	#[test]
+	fn userinfo_header_parses_the_known_keys() {
+		let info = SubscriptionInfo::from_userinfo_header(
+			"upload=455727941; download=6174315083; total=1073741824000; expire=1671815872",
+		)
+		.unwrap();
+		assert_eq!(
+			info,
+			SubscriptionInfo {
+				upload: 455727941,
+				download: 6174315083,
+				total: 1073741824000,
+				expire: 1671815872,
+			}
+		);
+		assert_eq!(info.used(), 6630043024);
+	}
+
+	#[test]
+	fn userinfo_header_accepts_floats_case_and_unknown_keys() {
+		let header = " Upload = 1.5e3 ;DOWNLOAD=20.9; plan=gold; total=; expire=-5";
+		let info = SubscriptionInfo::from_userinfo_header(header).unwrap();
+		assert_eq!(
+			info,
+			SubscriptionInfo {
+				upload: 1500,
+				download: 20,
+				total: 0,
+				expire: 0,
+			}
+		);
+		assert_eq!(info.used_ratio(), None);
+	}
+
+	#[test]
+	fn userinfo_header_without_known_keys_is_none() {
+		assert_eq!(SubscriptionInfo::from_userinfo_header(""), None);
+		assert_eq!(SubscriptionInfo::from_userinfo_header("plan=gold; total=lots"), None);
+	}
+
+	#[test]
+	fn groups_follow_the_global_order() {
+		let proxies: ProxiesResponse = serde_json::from_str(
+			r#"{ "proxies": {
+				"GLOBAL": { "type": "Selector", "all": ["Streaming", "Proxy", "DIRECT"] },
+				"Auto": { "type": "URLTest", "all": ["hk-1"] },
+				"Proxy": { "type": "Selector", "all": ["Auto", "hk-1"] },
+				"Fallback": { "type": "Fallback", "all": ["hk-1"] },
+				"Streaming": { "type": "Selector", "all": ["Proxy"] },
+				"hk-1": { "type": "Shadowsocks" },
+				"DIRECT": { "type": "Direct" }
+			} }"#,
+		)
+		.unwrap();
+
+		// Plain proxies are left out; groups missing from GLOBAL follow by name
+		let names: Vec<&str> = proxies.groups().into_iter().map(|(name, _)| name).collect();
+		assert_eq!(names, ["Streaming", "Proxy", "Auto", "Fallback", "GLOBAL"]);
+	}
+
+	#[test]
+	fn groups_without_global_sort_by_name() {
+		let proxies: ProxiesResponse =
+			serde_json::from_str(r#"{ "proxies": { "b": { "all": [] }, "a": { "all": [] }, "c": {} } }"#).unwrap();
+		let names: Vec<&str> = proxies.groups().into_iter().map(|(name, _)| name).collect();
+		assert_eq!(names, ["a", "b"]);
+	}
+
+	#[test]
+	fn path_segments_are_percent_encoded() {
+		assert_eq!(encode_path_segment("Proxy-1_a.b~c"), "Proxy-1_a.b~c");
+		assert_eq!(encode_path_segment("HK 01"), "HK%2001");
+		assert_eq!(encode_path_segment("a/b?c#d%"), "a%2Fb%3Fc%23d%25");
+		assert_eq!(encode_path_segment("香港"), "%E9%A6%99%E6%B8%AF");
+		assert_eq!(encode_path_segment("🚀 Fast"), "%F0%9F%9A%80%20Fast");
+	}
+
+	#[test]
+	fn drain_lines_keeps_a_partial_line() {
+		let mut buffer = b"{\"up\":1,\"down\":2}\n{\"up\":3,".to_vec();
+		let items: Vec<ApiResult<Traffic>> = drain_lines(&mut buffer);
+		assert_eq!(items.len(), 1);
+		let traffic = items[0].as_ref().unwrap();
+		assert_eq!((traffic.up, traffic.down), (1, 2));
+		assert_eq!(buffer, b"{\"up\":3,");
+
+		buffer.extend_from_slice(b"\"down\":4}\n");
+		let items: Vec<ApiResult<Traffic>> = drain_lines(&mut buffer);
+		let traffic = items[0].as_ref().unwrap();
+		assert_eq!((traffic.up, traffic.down), (3, 4));
+		assert!(buffer.is_empty());
+	}
+
+	#[test]
+	fn drain_lines_accepts_sse_and_skips_blank_lines() {
+		let mut buffer = b"data: {\"up\":5,\"down\":6}\r\n\r\n\n".to_vec();
+		let items: Vec<ApiResult<Traffic>> = drain_lines(&mut buffer);
+		assert_eq!(items.len(), 1);
+		let traffic = items[0].as_ref().unwrap();
+		assert_eq!((traffic.up, traffic.down), (5, 6));
+	}
+
+	#[test]
+	fn drain_lines_reports_bad_lines_and_continues() {
+		let mut buffer = b"not json\n{\"up\":7,\"down\":8}\n".to_vec();
+		let items: Vec<ApiResult<Traffic>> = drain_lines(&mut buffer);
+		assert!(matches!(items[0], Err(ClashApiError::Decode(_))));
+		assert!(items[1].is_ok());
 	}
 }
@@ -1,15 +1,43 @@
 // SPDX-License-Identifier: AGPL3.0
 
-use std::{collections::HashMap, time::Duration};
+use std::{
+	collections::{HashMap, HashSet, VecDeque},
+	path::PathBuf,
+	time::{Duration, Instant},
+};
 
 use cosmic::{
 	Application, Element, app::Task,
 	iced::{Length, Subscription},
 	widget, widget::nav_bar, theme,
 };
-use tokio::time::sleep;
+use futures_util::FutureExt;
+use crate::{
+	api::{
+		ClashApi, ClashApiError, ConfigPatch, ConnectionsSnapshot, LogEntry, LogLevel, Memory, ProviderKind,
+		ProxiesResponse, ProxyProvider, Rule, RuleProvider, RunMode, RuntimeConfig,
+	},
+	config::Config,
+	core_info::{self, CoreFeature, CoreFlavour, CoreInfo},
+	core_manager::{self, CoreUpdate, InstalledCore},
+	fl, overrides,
+	pages::rules::{self, RuleMatches},
+	preflight::ConfigIssue,
+	profiles::{self, FetchOutcome, Fetched, Profile, ProfileIndex, ProfileSource, RemoteSource, ScannedFile},
+	readiness::{self, Readiness, ReadinessError},
+	sidecar::{self, SidecarManager, SidecarStatus},
+	usage::{self, UsageStore},
+	widgets::traffic_chart::{self, ChartWindow, TrafficSample},
+};
+
+/// How many latency tests run at once when testing many proxies.
+const DELAY_TEST_CONCURRENCY: usize = 8;
+
+/// Scroll-back of the log page; older lines are dropped.
+const LOG_BUFFER_LINES: usize = 2000;
 
-use crate::{api::ClashApi, config::Config, fl, sidecar::SidecarManager};
+/// How often counted traffic is written to the usage store.
+const USAGE_FLUSH_INTERVAL: Duration = Duration::from_secs(60);
 
 /// The main application model.
 pub struct AppModel {
@@ -25,28 +53,135 @@ pub struct AppModel {
 	key_binds: HashMap<MenuKeyBind, MenuAction>,
 	/// Configuration data that persists between application runs.
 	pub config: Config,
+	/// Client for profile and core downloads, shared so connections are reused
+	http: reqwest::Client,
 	/// Toggle the VPN subscription
 	pub vpn_is_active: bool,
 	/// Clash sidecar manager
 	pub sidecar: Option<SidecarManager>,
+	/// Last status reported by the sidecar supervisor
+	pub sidecar_status: SidecarStatus,
+	/// Whether the core's controller is up yet
+	pub readiness: Readiness,
+	/// A core found running at startup that we did not spawn
+	pub existing_core: Option<ExistingCore>,
+	/// Pid of an adopted core, which has no sidecar manager
+	pub adopted_pid: Option<u32>,
+	/// A core stop still in flight, awaited before the app exits
+	stopping_core: Option<sidecar::Stopping>,
+	/// What the running core reported, dropped as a whole when it stops
+	pub session: Session,
+	/// What the configured core binary reported about itself
+	pub core_info: Option<Result<CoreInfo, String>>,
+	/// Progress of the managed core installer
+	pub core_update: CoreUpdateState,
+	/// Time range of the traffic chart
+	pub chart_window: ChartWindow,
+	/// Daily traffic totals per profile, persisted across runs
+	pub usage: UsageStore,
+	/// When `usage` was last written to disk
+	usage_flushed_at: Instant,
+	/// Proxy groups unfolded on the proxies page
+	pub expanded_groups: HashSet<String>,
+	/// Text the connections table is filtered by
+	pub connection_filter: String,
+	/// Column the connections table is sorted by
+	pub connection_sort: ConnectionSort,
+	pub connection_sort_descending: bool,
+	/// Text the rules list is searched by
+	pub rule_search: String,
+	/// Only show rules targeting this policy
+	pub rule_policy: Option<String>,
+	/// Recent core log lines, oldest first
+	pub logs: VecDeque<LogEntry>,
+	/// Level the log stream is followed at
+	pub log_level: LogLevel,
+	/// Stop following the log while reading it
+	pub logs_paused: bool,
+	/// Lines that arrived while paused, shown once the log is resumed
+	pub held_logs: VecDeque<LogEntry>,
+	/// Text the log lines are filtered by
+	pub log_filter: String,
+	/// Where the last export went, or why it failed
+	pub log_export: Option<Result<PathBuf, String>>,
+	/// Profiles in the config dir and what is known about them
+	pub profiles: ProfileIndex,
+	/// File stem of the last profile the core refused to load, with the reason
+	pub profile_issue: Option<(String, ConfigIssue)>,
+	/// Remote profiles with a download in flight
+	pub refreshing_profiles: HashSet<String>,
+	/// Why the last download of a remote profile failed, by name
+	pub profile_fetch_errors: HashMap<String, String>,
+	/// The add subscription form, while it is open
+	pub remote_form: Option<RemoteProfileForm>,
+	/// Profile whose name and notes are being edited
+	pub profile_edit: Option<ProfileEdit>,
+	/// Profile waiting for the user to confirm its deletion
+	pub deleting_profile: Option<String>,
+	/// Why the last duplicate or delete failed
+	pub profile_error: Option<String>,
+	/// The generated config the core runs, while it is shown
+	pub merged_config: Option<Result<String, String>>,
+	/// Why the override could not be opened
+	pub override_error: Option<String>,
+	/// Currently edited setting field
+	pub editing_setting: Option<SettingField>,
+	/// Value buffer for inline editing
+	pub edit_value: String,
+}
+
+/// State that only means something while a core runs. Stopping replaces it
+/// with its default, so nothing from the last run lingers.
+#[derive(Debug, Default)]
+pub struct Session {
 	/// Clash REST API client
 	pub api: Option<ClashApi>,
+	/// Last error the controller returned, shown until a call succeeds
+	pub api_error: Option<ClashApiError>,
+	/// Last failure of a controller stream, cleared when traffic flows again
+	pub stream_error: Option<ClashApiError>,
 	/// Latest fetched clash version
 	pub clash_version: Option<String>,
+	/// Settings of the running core, from `/configs`
+	pub runtime_config: Option<RuntimeConfig>,
 	/// Latest fetched traffic stats
 	pub traffic: Option<crate::api::Traffic>,
-	/// Discovered config profiles
-	pub profiles: Vec<String>,
-	/// Currently edited setting field
-	pub editing_setting: Option<SettingField>,
-	/// Value buffer for inline editing
-	pub edit_value: String,
+	/// Traffic samples of the last hour, oldest first
+	pub traffic_history: VecDeque<TrafficSample>,
+	/// Latest memory usage of the core
+	pub memory: Option<Memory>,
+	/// Latest `/proxies` snapshot
+	pub proxies: Option<ProxiesResponse>,
+	/// Latency results from tests run this session, by proxy name
+	pub delays: HashMap<String, Delay>,
+	/// Latest `/connections` snapshot, refreshed while home or the
+	/// connections page is open
+	pub connections: Option<ConnectionsSnapshot>,
+	/// Rules loaded by the running profile, in match order
+	pub rules: Option<Vec<Rule>>,
+	/// Policies the rules send traffic to, offered as a filter
+	pub rule_policies: Vec<String>,
+	/// Rules passing the search and policy filter, refreshed when either or
+	/// the rules change rather than on every redraw
+	pub rule_matches: RuleMatches,
+	/// Proxy providers of the running profile
+	pub proxy_providers: Option<Vec<ProxyProvider>>,
+	/// Rule providers of the running profile
+	pub rule_providers: Option<Vec<RuleProvider>>,
+	/// Providers with an update or health check in flight
+	pub busy_providers: HashSet<(ProviderKind, String)>,
 }
 
 /// Available context pages in the application.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ContextPage {
 	Home,
+	Proxies,
+	Connections,
+	Rules,
+	Providers,
+	Logs,
+	Usage,
 	Profile,
 	Settings,
 }
@@ -58,6 +193,99 @@ pub enum SettingField {
 	ConfigDir,
 	ApiPort,
 	ApiSecret,
+	MaxRestarts,
+	StopTimeout,
+	ManifestUrl,
+	DelayTestUrl,
+	DelayTimeout,
+	MonthlyQuota,
+}
+
+/// Columns the connections table can be sorted by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectionSort {
+	Destination,
+	Process,
+	Rule,
+	Chain,
+	Upload,
+	Download,
+	Start,
+}
+
+/// Outcome of a latency test for one proxy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Delay {
+	Testing,
+	Measured(u64),
+	Failed,
+}
+
+/// A clash core that was already running when the app started.
+#[derive(Debug, Clone)]
+pub struct ExistingCore {
+	/// Pid from the pidfile, if that process is still alive
+	pub pid: Option<u32>,
+	/// Version reported by the controller on the configured port
+	pub version: Option<String>,
+	/// The controller answered but rejected our API secret
+	pub unauthorized: bool,
+}
+
+/// Input of the add subscription form.
+#[derive(Debug, Clone, Default)]
+pub struct RemoteProfileForm {
+	pub name: String,
+	pub url: String,
+	pub user_agent: String,
+	pub refresh_hours: String,
+	/// Why the last submit was refused
+	pub error: Option<String>,
+}
+
+/// Fields of [`RemoteProfileForm`].
+#[derive(Debug, Clone, Copy)]
+pub enum RemoteFormField {
+	Name,
+	Url,
+	UserAgent,
+	RefreshHours,
+}
+
+/// Name and notes of a profile while they are edited.
+#[derive(Debug, Clone)]
+pub struct ProfileEdit {
+	pub file: String,
+	pub name: String,
+	pub notes: String,
+}
+
+/// Fields of [`ProfileEdit`].
+#[derive(Debug, Clone, Copy)]
+pub enum ProfileEditField {
+	Name,
+	Notes,
+}
+
+/// Where the managed core installer is at.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CoreUpdateState {
+	Idle,
+	Checking,
+	UpToDate,
+	Available(CoreUpdate),
+	Installing(CoreUpdate),
+	Installed(InstalledCore),
+	Failed(String),
+}
+
+/// What a config file is being validated for. `Select` and `Reload` carry the
+/// file stem of the profile, which is what `profile_issue` records.
+#[derive(Debug, Clone)]
+pub enum Activation {
+	Start,
+	Select(String),
+	Reload(String),
 }
 
 /// Messages emitted by the application and its widgets.
@@ -65,15 +293,95 @@ pub enum SettingField {
 pub enum Message {
 	ToggleVPN,
 	SelectProfile(String),
+	OpenRemoteForm,
+	RemoteFormChanged(RemoteFormField, String),
+	CancelRemoteForm,
+	AddRemoteProfile,
+	RefreshProfile(String),
+	RefreshDueProfiles,
+	ProfileFetched(String, Result<Fetched, String>),
+	EditProfile(String),
+	ProfileEditChanged(ProfileEditField, String),
+	SaveProfileEdit,
+	CancelProfileEdit,
+	DuplicateProfile(String),
+	ProfileDuplicated(Profile, Result<(), String>),
+	DeleteProfile(String),
+	ConfirmDeleteProfile,
+	CancelDeleteProfile,
+	ProfileDeleted(String, Result<(), String>),
+	EditOverride,
+	OverrideOpened(Result<(), String>),
+	ShowMergedConfig,
+	MergedConfigLoaded(Result<String, String>),
+	HideMergedConfig,
 	ReloadConfig,
-	ProfileScanResult(Vec<String>),
-	ClashVersionFetched(String),
+	ConfigValidated(Activation, Result<(), ConfigIssue>),
+	CoreInspected(Result<CoreInfo, String>),
+	CheckCoreUpdate,
+	CoreUpdateChecked(Result<Option<CoreUpdate>, String>),
+	InstallCoreUpdate,
+	CoreInstalled(Result<InstalledCore, String>),
+	RollbackCore,
+	ExistingCoreDetected(Option<ExistingCore>),
+	AdoptExistingCore,
+	KillExistingCore,
+	ExistingCoreKilled,
+	ChangeApiPort,
+	ChangeApiSecret,
+	SidecarStatusChanged(SidecarStatus),
+	ConfigReloaded(Result<(), ClashApiError>),
+	RefreshProxies,
+	ProxiesFetched(Result<ProxiesResponse, ClashApiError>),
+	ToggleProxyGroup(String),
+	SelectProxy { group: String, name: String },
+	ProxySelected(Result<(), ClashApiError>),
+	TestDelay(String),
+	TestGroupDelay(String),
+	TestAllDelays,
+	DelayTested(String, Result<u64, ClashApiError>),
+	GroupDelayTested(String, Result<HashMap<String, u64>, ClashApiError>),
+	ConnectionsUpdated(ConnectionsSnapshot),
+	ConnectionFilterChanged(String),
+	SortConnections(ConnectionSort),
+	CloseConnection(String),
+	CloseAllConnections,
+	ConnectionsClosed(Result<(), ClashApiError>),
+	RefreshRules,
+	RulesFetched(Result<Vec<Rule>, ClashApiError>),
+	RuleSearchChanged(String),
+	RulePolicySelected(Option<String>),
+	RefreshProviders,
+	ProxyProvidersFetched(Result<Vec<ProxyProvider>, ClashApiError>),
+	RuleProvidersFetched(Result<Vec<RuleProvider>, ClashApiError>),
+	UpdateProvider(ProviderKind, String),
+	HealthCheckProvider(String),
+	ProviderActionDone(ProviderKind, String, Result<(), ClashApiError>),
+	LogsReceived(Vec<LogEntry>),
+	SetLogLevel(LogLevel),
+	ToggleLogPause,
+	LogFilterChanged(String),
+	ClearLogs,
+	ExportLogs,
+	LogsExported(Result<PathBuf, String>),
+	RuntimeConfigFetched(Result<RuntimeConfig, ClashApiError>),
+	SetRunMode(RunMode),
+	RuntimeConfigPatched(Result<(), ClashApiError>),
+	ApiFailed(ClashApiError),
+	StreamFailed(ClashApiError),
+	ProfileScanResult(Result<Vec<ScannedFile>, String>),
+	CoreReady(String),
+	CoreFailed(ReadinessError),
+	CoreStopped(Option<ReadinessError>),
 	TrafficUpdated(crate::api::Traffic),
-	UpdateTraffic,
+	MemoryUpdated(Memory),
+	SetChartWindow(ChartWindow),
 	EditSetting(SettingField),
 	EditValueChanged(String),
 	SaveSetting,
 	CancelEdit,
+	Quit,
+	Exit,
 	Nop,
 }
 
@@ -82,12 +390,42 @@ impl std::fmt::Debug for AppModel {
 		f.debug_struct("AppModel")
 			.field("context_page", &self.context_page)
 			.field("config", &self.config)
+			.field("http", &self.http)
 			.field("vpn_is_active", &self.vpn_is_active)
 			.field("sidecar", &self.sidecar)
-			.field("api", &self.api)
-			.field("clash_version", &self.clash_version)
-			.field("traffic", &self.traffic)
+			.field("sidecar_status", &self.sidecar_status)
+			.field("readiness", &self.readiness)
+			.field("existing_core", &self.existing_core)
+			.field("adopted_pid", &self.adopted_pid)
+			.field("stopping_core", &self.stopping_core.is_some())
+			.field("session", &self.session)
+			.field("core_info", &self.core_info)
+			.field("core_update", &self.core_update)
+			.field("chart_window", &self.chart_window)
+			.field("usage", &self.usage)
+			.field("usage_flushed_at", &self.usage_flushed_at)
+			.field("expanded_groups", &self.expanded_groups)
+			.field("connection_filter", &self.connection_filter)
+			.field("connection_sort", &self.connection_sort)
+			.field("connection_sort_descending", &self.connection_sort_descending)
+			.field("rule_search", &self.rule_search)
+			.field("rule_policy", &self.rule_policy)
+			.field("logs", &self.logs.len())
+			.field("log_level", &self.log_level)
+			.field("logs_paused", &self.logs_paused)
+			.field("held_logs", &self.held_logs.len())
+			.field("log_filter", &self.log_filter)
+			.field("log_export", &self.log_export)
 			.field("profiles", &self.profiles)
+			.field("profile_issue", &self.profile_issue)
+			.field("refreshing_profiles", &self.refreshing_profiles)
+			.field("profile_fetch_errors", &self.profile_fetch_errors)
+			.field("remote_form", &self.remote_form)
+			.field("profile_edit", &self.profile_edit)
+			.field("deleting_profile", &self.deleting_profile)
+			.field("profile_error", &self.profile_error)
+			.field("merged_config", &self.merged_config)
+			.field("override_error", &self.override_error)
 			.field("editing_setting", &self.editing_setting)
 			.field("edit_value", &self.edit_value)
 			.finish()
@@ -114,23 +452,50 @@ impl Application for AppModel {
 		core.window.content_container = false;
 
 		let config = Config::load().unwrap_or_default();
-		let sidecar = Some(SidecarManager::new(
-			config.clash_binary(),
-			config.config_dir(),
-			config.config_dir().join("config.yaml"),
-		));
+		let profiles = ProfileIndex::load().unwrap_or_else(|err| {
+			tracing::warn!("Failed to load the profile index: {}", err);
+			ProfileIndex::default()
+		});
+		let sidecar = Some(build_sidecar(&config));
 
 		let mut nav = nav_bar::Model::default();
 		nav.insert()
 			.icon(widget::icon::from_name("user-home-symbolic"))
 			.text(fl!("home"))
+			.data(ContextPage::Home)
 			.activate();
+		nav.insert()
+			.icon(widget::icon::from_name("network-workgroup-symbolic"))
+			.text(fl!("proxies"))
+			.data(ContextPage::Proxies);
+		nav.insert()
+			.icon(widget::icon::from_name("network-transmit-receive-symbolic"))
+			.text(fl!("connections"))
+			.data(ContextPage::Connections);
+		nav.insert()
+			.icon(widget::icon::from_name("view-list-symbolic"))
+			.text(fl!("rules"))
+			.data(ContextPage::Rules);
+		nav.insert()
+			.icon(widget::icon::from_name("folder-download-symbolic"))
+			.text(fl!("providers"))
+			.data(ContextPage::Providers);
+		nav.insert()
+			.icon(widget::icon::from_name("utilities-terminal-symbolic"))
+			.text(fl!("logs"))
+			.data(ContextPage::Logs);
+		nav.insert()
+			.icon(widget::icon::from_name("utilities-system-monitor-symbolic"))
+			.text(fl!("usage"))
+			.data(ContextPage::Usage);
 		nav.insert()
 			.icon(widget::icon::from_name("folder-open-symbolic"))
-			.text(fl!("profile"));
+			.text(fl!("profile"))
+			.data(ContextPage::Profile);
 		nav.insert()
 			.icon(widget::icon::from_name("preferences-system-symbolic"))
-			.text(fl!("settings"));
+			.text(fl!("settings"))
+			.data(ContextPage::Settings);
 
 		let mut app = Self {
 			core,
@@ -138,18 +503,53 @@ impl Application for AppModel {
 			nav,
 			key_binds: HashMap::new(),
 			config,
+			http: reqwest::Client::new(),
 			vpn_is_active: false,
 			sidecar,
-			api: None,
-			clash_version: None,
-			traffic: None,
-			profiles: Vec::new(),
+			sidecar_status: SidecarStatus::Stopped,
+			readiness: Readiness::Stopped,
+			existing_core: None,
+			adopted_pid: None,
+			stopping_core: None,
+			session: Session::default(),
+			core_info: None,
+			core_update: CoreUpdateState::Idle,
+			chart_window: ChartWindow::default(),
+			usage: UsageStore::load(),
+			usage_flushed_at: Instant::now(),
+			expanded_groups: HashSet::new(),
+			connection_filter: String::new(),
+			connection_sort: ConnectionSort::Start,
+			connection_sort_descending: true,
+			rule_search: String::new(),
+			rule_policy: None,
+			logs: VecDeque::with_capacity(LOG_BUFFER_LINES),
+			log_level: LogLevel::default(),
+			logs_paused: false,
+			held_logs: VecDeque::new(),
+			log_filter: String::new(),
+			log_export: None,
+			profiles,
+			profile_issue: None,
+			refreshing_profiles: HashSet::new(),
+			profile_fetch_errors: HashMap::new(),
+			remote_form: None,
+			profile_edit: None,
+			deleting_profile: None,
+			profile_error: None,
+			merged_config: None,
+			override_error: None,
 			editing_setting: None,
 			edit_value: String::new(),
 		};
 
 		// Initial tasks
-		let task = Task::batch(vec![app.update_title(), app.scan_profiles()]);
+		let task = Task::batch(vec![
+			app.update_title(),
+			app.scan_profiles(),
+			app.inspect_core(),
+			app.detect_existing_core(),
+		]);
 
 		(app, task)
 	}
@@ -157,107 +557,1006 @@ impl Application for AppModel {
 	fn update(&mut self, message: Self::Message) -> Task<Self::Message> {
 		match message {
 			Message::ToggleVPN => {
-				if self.vpn_is_active {
+				// The old core may still hold the port during its grace period
+				if self.readiness == Readiness::Stopping {
+					return Task::none();
+				}
+				if self.vpn_is_active || self.readiness == Readiness::Starting {
 					// Stop VPN
+					self.vpn_is_active = false;
+					self.sidecar_status = SidecarStatus::Stopped;
+					self.readiness = Readiness::Stopped;
+					self.flush_usage();
+					self.session = Session::default();
+					if let Some(pid) = self.adopted_pid.take() {
+						let binary = self.config.clash_binary();
+						let work_dir = self.config.config_dir();
+						let grace_period = self.config.stop_grace_period();
+						let stopping = sidecar::terminate_pid(pid, binary, work_dir, grace_period).boxed().shared();
+						return self.wait_for_stop(stopping, None);
+					}
 					if let Some(mut sidecar) = self.sidecar.take() {
-						let _ = sidecar.stop();
+						let stopping = sidecar.stop();
+						return self.wait_for_stop(stopping, None);
 					}
-					self.vpn_is_active = false;
-					self.api = None;
-					self.clash_version = None;
-					self.traffic = None;
-				} else {
-					// Start VPN
-					let binary = self.config.clash_binary();
-					let work_dir = self.config.config_dir();
-					let config_path = work_dir.join("config.yaml");
-
-					let mut sidecar = SidecarManager::new(binary, work_dir, config_path);
-					if let Ok(()) = sidecar.start() {
-						self.sidecar = Some(sidecar);
-						self.vpn_is_active = true;
-
-						// Create API client
-						let api = ClashApi::new(self.config.api_url(), self.config.api_secret.clone());
-						self.api = Some(api.clone());
-
-						// Fetch version after a short delay
-						let api_clone = api.clone();
+				} else if self.existing_core.is_none() {
+					// Start VPN once the core accepts the config
+					self.readiness = Readiness::Starting;
+					let path = self.config.active_profile_path();
+					return self.validate_config(Activation::Start, path);
+				}
+				Task::none()
+			}
+			Message::ConfigValidated(activation, result) => match (activation, result) {
+				(Activation::Start, Ok(())) => {
+					// The user may have cancelled while the check ran
+					if self.readiness == Readiness::Starting {
+						return self.start_sidecar();
+					}
+					Task::none()
+				}
+				(Activation::Start, Err(issue)) => {
+					if self.readiness == Readiness::Starting {
+						self.readiness = Readiness::Failed(ReadinessError::InvalidConfig(issue));
+					}
+					Task::none()
+				}
+				(Activation::Select(profile), Ok(())) => {
+					self.profile_issue = None;
+					self.config.active_profile = Some(profile.clone());
+					let _ = self.config.save();
+					self.mark_profile_used();
+					// Also reload the selected profile in clash
+					if let Some(api) = &self.session.api {
+						let api = api.clone();
+						let path = self.config.runtime_path().to_string_lossy().to_string();
 						return Task::perform(
-							async move {
-								sleep(Duration::from_millis(500)).await;
-								match api_clone.version().await {
-									Ok(v) => Message::ClashVersionFetched(v.version.unwrap_or_default()),
-									Err(_) => Message::Nop,
-								}
-							},
+							async move { Message::ConfigReloaded(api.reload_config(&path).await) },
 							|msg| cosmic::Action::App(msg),
 						);
 					}
+					Task::none()
+				}
+				(Activation::Reload(_), Ok(())) => {
+					self.profile_issue = None;
+					if let Some(api) = &self.session.api {
+						// Background reload
+						let api = api.clone();
+						let path = self.config.runtime_path().to_string_lossy().to_string();
+						return Task::perform(
+							async move { Message::ConfigReloaded(api.reload_config(&path).await) },
+							|msg| cosmic::Action::App(msg),
+						);
+					}
+					Task::none()
+				}
+				(Activation::Select(profile), Err(issue)) => {
+					self.profile_issue = Some((profile, issue));
+					Task::none()
+				}
+				(Activation::Reload(profile), Err(issue)) => {
+					self.profile_issue = Some((profile, issue));
+					Task::none()
+				}
+			},
+			Message::CoreStopped(failure) => {
+				self.stopping_core = None;
+				if self.readiness == Readiness::Stopping {
+					self.readiness = match failure {
+						Some(err) => Readiness::Failed(err),
+						None => Readiness::Stopped,
+					};
+				}
+				Task::none()
+			}
+			Message::CoreReady(version) => {
+				// A late answer after the user cancelled the start is ignored
+				if self.readiness == Readiness::Starting {
+					self.readiness = Readiness::Ready;
+					self.vpn_is_active = true;
+					self.session.api = Some(ClashApi::new(self.config.api_url(), self.config.api_secret.clone()));
+					self.session.clash_version = Some(version);
+					self.mark_profile_used();
+					return self.on_page_shown();
+				}
+				Task::none()
+			}
+			Message::CoreFailed(err) => {
+				if self.readiness == Readiness::Starting {
+					tracing::error!("Clash core failed to become ready: {}", err);
+					// Keep the manager around so its output stays visible
+					if let Some(sidecar) = &mut self.sidecar {
+						let stopping = sidecar.stop();
+						return self.wait_for_stop(stopping, Some(err));
+					}
+					self.readiness = Readiness::Failed(err);
 				}
 				Task::none()
 			}
 			Message::SelectProfile(profile) => {
-				self.config.active_profile = Some(profile.clone());
-				let _ = self.config.save();
-				// Also reload the selected profile in clash
-				if let Some(api) = &self.api {
-					let api = api.clone();
-					let config_dir = self.config.config_dir();
-					let path = config_dir.join(format!("{}.yaml", profile)).to_string_lossy().to_string();
+				// Refuse the switch if the core would reject the file
+				let path = self.config.profile_path(Some(&profile));
+				self.validate_config(Activation::Select(profile), path)
+			}
+			Message::OpenRemoteForm => {
+				self.remote_form = Some(RemoteProfileForm {
+					refresh_hours: profiles::DEFAULT_REFRESH_HOURS.to_string(),
+					..RemoteProfileForm::default()
+				});
+				Task::none()
+			}
+			Message::RemoteFormChanged(field, value) => {
+				if let Some(form) = &mut self.remote_form {
+					match field {
+						RemoteFormField::Name => form.name = value,
+						RemoteFormField::Url => form.url = value,
+						RemoteFormField::UserAgent => form.user_agent = value,
+						RemoteFormField::RefreshHours => form.refresh_hours = value,
+					}
+					form.error = None;
+				}
+				Task::none()
+			}
+			Message::CancelRemoteForm => {
+				self.remote_form = None;
+				Task::none()
+			}
+			Message::AddRemoteProfile => {
+				let Some(form) = &mut self.remote_form else {
+					return Task::none();
+				};
+				let name = form.name.trim().to_string();
+				let url = form.url.trim().to_string();
+				if let Err(err) = profiles::validate_name(&name) {
+					form.error = Some(err.to_string());
+					return Task::none();
+				}
+				// Never replace another profile or its file
+				if self.profiles.get(&name).is_some() || self.config.profile_path(Some(&name)).exists() {
+					form.error = Some(fl!("profile-name-taken", name = name));
+					return Task::none();
+				}
+				if !(url.starts_with("http://") || url.starts_with("https://")) {
+					form.error = Some(fl!("subscription-url-invalid"));
+					return Task::none();
+				}
+				let Ok(refresh_hours) = form.refresh_hours.trim().parse() else {
+					form.error = Some(fl!("refresh-hours-invalid"));
+					return Task::none();
+				};
+				let user_agent = Some(form.user_agent.trim().to_string()).filter(|ua| !ua.is_empty());
+
+				self.remote_form = None;
+				let source = ProfileSource::Remote(RemoteSource::new(url, user_agent, refresh_hours));
+				self.profiles
+					.insert(Profile::new(name.clone(), name.clone(), source, profiles::unix_now()));
+				self.save_profiles();
+				self.update(Message::RefreshProfile(name))
+			}
+			Message::RefreshProfile(name) => {
+				let Some(profile) = self.profiles.get(&name).filter(|profile| profile.remote().is_some()).cloned() else {
+					return Task::none();
+				};
+				if !self.refreshing_profiles.insert(name.clone()) {
+					return Task::none();
+				}
+				self.profile_fetch_errors.remove(&name);
+				let config_dir = self.config.config_dir();
+				let client = self.http.clone();
+				Task::perform(
+					async move {
+						let result = profiles::fetch(&client, &profile, &config_dir)
+							.await
+							.map_err(|err| err.to_string());
+						Message::ProfileFetched(name, result)
+					},
+					|msg| cosmic::Action::App(msg),
+				)
+			}
+			Message::RefreshDueProfiles => {
+				let due: Vec<String> = self
+					.profiles
+					.due(profiles::unix_now())
+					.map(|profile| profile.file.clone())
+					.collect();
+				Task::batch(due.into_iter().map(|name| self.update(Message::RefreshProfile(name))))
+			}
+			Message::ProfileFetched(name, result) => {
+				self.refreshing_profiles.remove(&name);
+				match result {
+					Ok(fetched) => {
+						if let Some(remote) = self.profiles.get_mut(&name).and_then(Profile::remote_mut) {
+							remote.apply(&fetched, profiles::unix_now());
+							self.save_profiles();
+						}
+						if matches!(fetched.outcome, FetchOutcome::Updated { .. }) {
+							let mut tasks = vec![self.scan_profiles()];
+							// Hand the running core the new version of its profile
+							if self.session.api.is_some() && self.config.active_profile.as_deref() == Some(name.as_str()) {
+								tasks.push(self.update(Message::SelectProfile(name)));
+							}
+							return Task::batch(tasks);
+						}
+					}
+					Err(err) => {
+						tracing::warn!("Failed to download profile {:?}: {}", name, err);
+						if let Some(remote) = self.profiles.get_mut(&name).and_then(Profile::remote_mut) {
+							remote.fail(profiles::unix_now());
+							self.save_profiles();
+						}
+						self.profile_fetch_errors.insert(name, err);
+					}
+				}
+				Task::none()
+			}
+			Message::EditProfile(file) => {
+				self.profile_edit = self.profiles.get(&file).map(|profile| ProfileEdit {
+					file: profile.file.clone(),
+					name: profile.name.clone(),
+					notes: profile.notes.clone(),
+				});
+				Task::none()
+			}
+			Message::ProfileEditChanged(field, value) => {
+				if let Some(edit) = &mut self.profile_edit {
+					match field {
+						ProfileEditField::Name => edit.name = value,
+						ProfileEditField::Notes => edit.notes = value,
+					}
+				}
+				Task::none()
+			}
+			Message::SaveProfileEdit => {
+				if let Some(edit) = self.profile_edit.take() {
+					if let Some(profile) = self.profiles.get_mut(&edit.file) {
+						// An empty name falls back to the file name
+						let name = edit.name.trim();
+						profile.name = if name.is_empty() { edit.file.clone() } else { name.to_string() };
+						profile.notes = edit.notes.trim().to_string();
+						self.save_profiles();
+					}
+				}
+				Task::none()
+			}
+			Message::CancelProfileEdit => {
+				self.profile_edit = None;
+				Task::none()
+			}
+			Message::DuplicateProfile(file) => {
+				let Some(original) = self.profiles.get(&file).filter(|profile| profile.is_downloaded()).cloned() else {
+					return Task::none();
+				};
+				let config_dir = self.config.config_dir();
+				let copy_file = self.profiles.unique_file(&config_dir, &format!("{}-copy", original.file));
+				let mut copy = Profile::new(
+					copy_file.clone(),
+					fl!("profile-copy-name", name = original.name.clone()),
+					ProfileSource::Local,
+					profiles::unix_now(),
+				);
+				copy.notes = original.notes.clone();
+				self.profile_error = None;
+				Task::perform(
+					async move {
+						let result = profiles::duplicate(&config_dir, &original, &copy_file)
+							.await
+							.map_err(|err| err.to_string());
+						Message::ProfileDuplicated(copy, result)
+					},
+					|msg| cosmic::Action::App(msg),
+				)
+			}
+			Message::ProfileDuplicated(profile, result) => match result {
+				Ok(()) => {
+					self.profiles.insert(profile);
+					self.save_profiles();
+					self.scan_profiles()
+				}
+				Err(err) => {
+					tracing::warn!("Failed to duplicate profile: {}", err);
+					self.profile_error = Some(fl!("profile-duplicate-failed", reason = err));
+					Task::none()
+				}
+			},
+			Message::DeleteProfile(file) => {
+				self.deleting_profile = Some(file);
+				Task::none()
+			}
+			Message::CancelDeleteProfile => {
+				self.deleting_profile = None;
+				Task::none()
+			}
+			Message::ConfirmDeleteProfile => {
+				let Some(file) = self.deleting_profile.take() else {
+					return Task::none();
+				};
+				// The running profile stays until another one is selected
+				if self.config.active_profile.as_deref() == Some(file.as_str()) {
+					self.profile_error = Some(fl!("profile-delete-active"));
+					return Task::none();
+				}
+				let Some(profile) = self.profiles.get(&file).cloned() else {
+					return Task::none();
+				};
+				let config_dir = self.config.config_dir();
+				self.profile_error = None;
+				Task::perform(
+					async move {
+						let result = profiles::delete(&config_dir, &profile)
+							.await
+							.map_err(|err| err.to_string());
+						Message::ProfileDeleted(file, result)
+					},
+					|msg| cosmic::Action::App(msg),
+				)
+			}
+			Message::ProfileDeleted(file, result) => {
+				match result {
+					Ok(()) => {
+						self.profiles.remove(&file);
+						self.profile_fetch_errors.remove(&file);
+						self.save_profiles();
+					}
+					Err(err) => {
+						tracing::warn!("Failed to delete profile {:?}: {}", file, err);
+						self.profile_error = Some(fl!("profile-delete-failed", reason = err));
+					}
+				}
+				Task::none()
+			}
+			Message::EditOverride => {
+				self.override_error = None;
+				Task::perform(
+					async move {
+						let result = overrides::ensure()
+							.await
+							.and_then(|path| Ok(open::that_detached(path)?))
+							.map_err(|err| err.to_string());
+						Message::OverrideOpened(result)
+					},
+					|msg| cosmic::Action::App(msg),
+				)
+			}
+			Message::OverrideOpened(result) => {
+				if let Err(err) = result {
+					tracing::warn!("Failed to open the override: {}", err);
+					self.override_error = Some(err);
+				}
+				Task::none()
+			}
+			Message::ShowMergedConfig => {
+				// What the core was last given, not a fresh merge that may differ from it
+				let path = self.config.runtime_path();
+				Task::perform(
+					async move {
+						let result = match tokio::fs::read_to_string(&path).await {
+							Ok(merged) => Ok(merged),
+							Err(err) if err.kind() == std::io::ErrorKind::NotFound => Err(fl!("merged-config-missing")),
+							Err(err) => Err(format!("{}: {}", path.display(), err)),
+						};
+						Message::MergedConfigLoaded(result)
+					},
+					|msg| cosmic::Action::App(msg),
+				)
+			}
+			Message::MergedConfigLoaded(result) => {
+				self.merged_config = Some(result);
+				Task::none()
+			}
+			Message::HideMergedConfig => {
+				self.merged_config = None;
+				Task::none()
+			}
+			Message::ReloadConfig => {
+				if self.session.api.is_some() {
+					let path = self.config.active_profile_path();
+					let profile = self.config.active_profile.as_deref().unwrap_or(profiles::DEFAULT_FILE);
+					return self.validate_config(Activation::Reload(profile.to_string()), path);
+				}
+				Task::none()
+			}
+			Message::CoreInspected(result) => {
+				match &result {
+					Ok(info) if !info.is_supported() => tracing::warn!(
+						"{} {:?} is older than the supported minimum {:?}",
+						info.flavour,
+						info.version,
+						info.minimum_version()
+					),
+					Ok(_) => {}
+					Err(err) => tracing::warn!("Could not identify clash core: {}", err),
+				}
+				self.core_info = Some(result);
+				Task::none()
+			}
+			Message::CheckCoreUpdate => {
+				let Some(url) = self.config.core_manifest_url.clone() else {
+					return Task::none();
+				};
+				self.core_update = CoreUpdateState::Checking;
+				let installed = match &self.core_info {
+					Some(Ok(info)) => info.version.clone(),
+					_ => None,
+				};
+				let client = self.http.clone();
+				Task::perform(
+					async move {
+						let result = core_manager::fetch_manifest(&client, &url)
+							.await
+							.map(|manifest| {
+								core_manager::latest_for(&manifest, &core_manager::host_target())
+									.filter(|update| installed.as_ref().is_none_or(|v| update.version > *v))
+							})
+							.map_err(|err| err.to_string());
+						Message::CoreUpdateChecked(result)
+					},
+					|msg| cosmic::Action::App(msg),
+				)
+			}
+			Message::CoreUpdateChecked(result) => {
+				self.core_update = match result {
+					Ok(Some(update)) => CoreUpdateState::Available(update),
+					Ok(None) => CoreUpdateState::UpToDate,
+					Err(err) => CoreUpdateState::Failed(err),
+				};
+				Task::none()
+			}
+			Message::InstallCoreUpdate => {
+				let CoreUpdateState::Available(update) = &self.core_update else {
+					return Task::none();
+				};
+				let update = update.clone();
+				self.core_update = CoreUpdateState::Installing(update.clone());
+				let client = self.http.clone();
+				Task::perform(
+					async move {
+						let result = core_manager::install(&client, &update, &core_manager::cores_dir())
+							.await
+							.map_err(|err| err.to_string());
+						Message::CoreInstalled(result)
+					},
+					|msg| cosmic::Action::App(msg),
+				)
+			}
+			Message::CoreInstalled(result) => match result {
+				Ok(installed) => {
+					// The previous binary stays on disk for rollback
+					self.config.switch_binary(installed.binary_path.clone());
+					let _ = self.config.save();
+					self.core_update = CoreUpdateState::Installed(installed);
+					self.core_info = None;
+					self.inspect_core()
+				}
+				Err(err) => {
+					tracing::error!("Failed to install clash core: {}", err);
+					self.core_update = CoreUpdateState::Failed(err);
+					Task::none()
+				}
+			},
+			Message::RollbackCore => {
+				if self.config.rollback_binary() {
+					let _ = self.config.save();
+					self.core_update = CoreUpdateState::Idle;
+					self.core_info = None;
+					return self.inspect_core();
+				}
+				Task::none()
+			}
+			Message::ExistingCoreDetected(existing) => {
+				if let Some(core) = &existing {
+					tracing::warn!(
+						"Found a running clash core (pid {:?}, version {:?}, unauthorized {})",
+						core.pid,
+						core.version,
+						core.unauthorized
+					);
+				}
+				self.existing_core = existing;
+				Task::none()
+			}
+			Message::AdoptExistingCore => {
+				// Attach to the running core instead of spawning a second one.
+				// Without a pid it could never be stopped, so only the port is offered.
+				if let Some(core) = self.existing_core.take_if(|core| core.pid.is_some() && core.version.is_some()) {
+					self.adopted_pid = core.pid;
+					self.session.api = Some(ClashApi::new(self.config.api_url(), self.config.api_secret.clone()));
+					self.session.clash_version = core.version;
+					self.readiness = Readiness::Ready;
+					self.vpn_is_active = true;
+					return self.on_page_shown();
+				}
+				Task::none()
+			}
+			Message::KillExistingCore => {
+				if let Some(pid) = self.existing_core.as_ref().and_then(|core| core.pid) {
+					let binary = self.config.clash_binary();
+					let work_dir = self.config.config_dir();
+					let grace_period = self.config.stop_grace_period();
 					return Task::perform(
 						async move {
-							let _ = api.reload_config(&path).await;
-							Message::Nop
+							sidecar::terminate_pid(pid, binary, work_dir, grace_period).await;
+							Message::ExistingCoreKilled
 						},
 						|msg| cosmic::Action::App(msg),
 					);
 				}
 				Task::none()
 			}
-			Message::ReloadConfig => {
-				if let Some(api) = &self.api {
-					let api = api.clone();
-					let path = self
-						.config
-						.active_profile
-						.clone()
-						.unwrap_or_else(|| self.config.config_dir().join("config.yaml").to_string_lossy().to_string());
-
-					// Background reload
-					let api_clone = api.clone();
-					let path_clone = path.clone();
-					tokio::spawn(async move {
-						let _ = api_clone.reload_config(&path_clone).await;
-					});
+			Message::ExistingCoreKilled => {
+				self.existing_core = None;
+				Task::none()
+			}
+			Message::ChangeApiPort => {
+				// Leave the other core alone and move ours to a free port
+				self.existing_core = None;
+				let shown = self.show_page(ContextPage::Settings);
+				Task::batch([shown, self.update(Message::EditSetting(SettingField::ApiPort))])
+			}
+			Message::ChangeApiSecret => {
+				// Keep the card up; saving the secret probes the core again
+				let shown = self.show_page(ContextPage::Settings);
+				Task::batch([shown, self.update(Message::EditSetting(SettingField::ApiSecret))])
+			}
+			Message::SidecarStatusChanged(status) => {
+				if let SidecarStatus::Crashed { exit_code } = &status {
+					tracing::error!("Clash core crashed for good (exit code {:?})", exit_code);
 				}
+				self.sidecar_status = status;
 				Task::none()
 			}
-			Message::ProfileScanResult(profiles) => {
-				self.profiles = profiles;
+			Message::ProfileScanResult(Err(err)) => {
+				tracing::warn!("Failed to scan the config dir for profiles: {}", err);
 				Task::none()
 			}
-			Message::ClashVersionFetched(version) => {
-				self.clash_version = Some(version);
+			Message::ProfileScanResult(Ok(scanned)) => {
+				let before = self.profiles.clone();
+				self.profiles.sync(&scanned, profiles::unix_now());
+				if self.profiles != before {
+					self.save_profiles();
+				}
 				Task::none()
 			}
 			Message::TrafficUpdated(traffic) => {
-				self.traffic = Some(traffic);
+				// `/traffic` reports a rate about once a second; book it for the
+				// time since the previous sample
+				let elapsed = self
+					.session
+					.traffic_history
+					.back()
+					.map_or(Duration::from_secs(1), |last| last.at.elapsed());
+				let profile = self.config.active_profile.as_deref().unwrap_or(profiles::DEFAULT_FILE);
+				self.usage
+					.record_rate(&usage::today(), profile, traffic.up, traffic.down, elapsed);
+				if self.usage_flushed_at.elapsed() >= USAGE_FLUSH_INTERVAL {
+					self.flush_usage();
+				}
+
+				traffic_chart::record(
+					&mut self.session.traffic_history,
+					TrafficSample {
+						at: Instant::now(),
+						up: traffic.up,
+						down: traffic.down,
+					},
+				);
+				self.session.traffic = Some(traffic);
+				// Only the streams' own error; one from an action stays until it succeeds
+				self.session.stream_error = None;
+				Task::none()
+			}
+			Message::SetChartWindow(window) => {
+				self.chart_window = window;
 				Task::none()
 			}
-			Message::UpdateTraffic => {
-				if let Some(api) = &self.api {
-					let api_clone = api.clone();
+			Message::MemoryUpdated(memory) => {
+				self.session.memory = Some(memory);
+				Task::none()
+			}
+			Message::ConfigReloaded(result) => {
+				match result {
+					Ok(()) => {
+						// The new profile brings its own groups and rules
+						self.session.api_error = None;
+						self.session.proxies = None;
+						self.session.rules = None;
+						self.session.proxy_providers = None;
+						self.session.rule_providers = None;
+						self.on_page_shown()
+					}
+					Err(err) => self.update(Message::ApiFailed(err)),
+				}
+			}
+			Message::RefreshProxies => {
+				if let Some(api) = &self.session.api {
+					let api = api.clone();
+					return Task::perform(
+						async move { Message::ProxiesFetched(api.proxies().await) },
+						|msg| cosmic::Action::App(msg),
+					);
+				}
+				Task::none()
+			}
+			Message::ProxiesFetched(result) => match result {
+				Ok(proxies) => {
+					self.session.proxies = Some(proxies);
+					self.session.api_error = None;
+					Task::none()
+				}
+				Err(err) => self.update(Message::ApiFailed(err)),
+			},
+			Message::ToggleProxyGroup(group) => {
+				if !self.expanded_groups.remove(&group) {
+					self.expanded_groups.insert(group);
+				}
+				Task::none()
+			}
+			Message::SelectProxy { group, name } => {
+				if let Some(api) = &self.session.api {
+					let api = api.clone();
+					return Task::perform(
+						async move { Message::ProxySelected(api.select_proxy(&group, &name).await) },
+						|msg| cosmic::Action::App(msg),
+					);
+				}
+				Task::none()
+			}
+			Message::ProxySelected(result) => match result {
+				Ok(()) => self.update(Message::RefreshProxies),
+				Err(err) => self.update(Message::ApiFailed(err)),
+			},
+			Message::TestDelay(name) => {
+				let Some(api) = self.session.api.clone() else {
+					return Task::none();
+				};
+				self.session.delays.insert(name.clone(), Delay::Testing);
+				let test = self.config.delay_test();
+				Task::perform(
+					async move {
+						let result = api.proxy_delay(&name, &test).await;
+						Message::DelayTested(name, result)
+					},
+					|msg| cosmic::Action::App(msg),
+				)
+			}
+			Message::TestGroupDelay(group) => {
+				let Some(api) = self.session.api.clone() else {
+					return Task::none();
+				};
+				let members = self.group_members(&group);
+				for member in &members {
+					self.session.delays.insert(member.clone(), Delay::Testing);
+				}
+				let test = self.config.delay_test();
+				if self.supports(CoreFeature::GroupDelay) {
 					return Task::perform(
 						async move {
-							match api_clone.traffic().await {
-								Ok(traffic) => Message::TrafficUpdated(traffic),
-								Err(_) => Message::Nop,
-							}
+							let result = api.group_delay(&group, &test).await;
+							Message::GroupDelayTested(group, result)
 						},
 						|msg| cosmic::Action::App(msg),
 					);
 				}
+				self.test_delays(api, members)
+			}
+			Message::TestAllDelays => {
+				let Some(api) = self.session.api.clone() else {
+					return Task::none();
+				};
+				let groups = self.session.proxies.as_ref().map(|p| p.groups()).unwrap_or_default();
+				let mut names: Vec<String> = Vec::new();
+				for (_, group) in groups {
+					for member in group.all.iter().flatten() {
+						if !names.contains(member) {
+							names.push(member.clone());
+						}
+					}
+				}
+				for name in &names {
+					self.session.delays.insert(name.clone(), Delay::Testing);
+				}
+				self.test_delays(api, names)
+			}
+			Message::DelayTested(name, result) => {
+				let delay = match result {
+					Ok(delay) => Delay::Measured(delay),
+					// A proxy that times out or errors is a result, not an API failure
+					Err(ClashApiError::Status { .. } | ClashApiError::Timeout) => Delay::Failed,
+					Err(err) => {
+						self.session.delays.insert(name, Delay::Failed);
+						return self.update(Message::ApiFailed(err));
+					}
+				};
+				self.session.delays.insert(name, delay);
+				Task::none()
+			}
+			Message::GroupDelayTested(group, result) => {
+				let members = self.group_members(&group);
+				match result {
+					Ok(delays) => {
+						for member in members {
+							let delay = match delays.get(&member) {
+								Some(delay) if *delay > 0 => Delay::Measured(*delay),
+								_ => Delay::Failed,
+							};
+							self.session.delays.insert(member, delay);
+						}
+						Task::none()
+					}
+					Err(err) => {
+						for member in members {
+							self.session.delays.insert(member, Delay::Failed);
+						}
+						match err {
+							ClashApiError::Status { .. } | ClashApiError::Timeout => Task::none(),
+							err => self.update(Message::ApiFailed(err)),
+						}
+					}
+				}
+			}
+			Message::ConnectionsUpdated(snapshot) => {
+				self.session.connections = Some(snapshot);
+				Task::none()
+			}
+			Message::ConnectionFilterChanged(filter) => {
+				self.connection_filter = filter;
+				Task::none()
+			}
+			Message::SortConnections(column) => {
+				if self.connection_sort == column {
+					self.connection_sort_descending = !self.connection_sort_descending;
+				} else {
+					self.connection_sort = column;
+					// Numbers are most useful largest first, text A to Z
+					self.connection_sort_descending =
+						matches!(column, ConnectionSort::Upload | ConnectionSort::Download | ConnectionSort::Start);
+				}
+				Task::none()
+			}
+			Message::CloseConnection(id) => {
+				let Some(api) = self.session.api.clone() else {
+					return Task::none();
+				};
+				// Drop the row right away; the next snapshot confirms it
+				if let Some(snapshot) = &mut self.session.connections {
+					snapshot.connections.retain(|conn| conn.id != id);
+				}
+				Task::perform(
+					async move { Message::ConnectionsClosed(api.close_connection(&id).await) },
+					|msg| cosmic::Action::App(msg),
+				)
+			}
+			Message::CloseAllConnections => {
+				let Some(api) = self.session.api.clone() else {
+					return Task::none();
+				};
+				if let Some(snapshot) = &mut self.session.connections {
+					snapshot.connections.clear();
+				}
+				Task::perform(
+					async move { Message::ConnectionsClosed(api.close_all_connections().await) },
+					|msg| cosmic::Action::App(msg),
+				)
+			}
+			Message::ConnectionsClosed(result) => match result {
+				Ok(()) => Task::none(),
+				Err(err) => self.update(Message::ApiFailed(err)),
+			},
+			Message::RefreshRules => {
+				if let Some(api) = &self.session.api {
+					let api = api.clone();
+					return Task::perform(
+						async move { Message::RulesFetched(api.rules().await) },
+						|msg| cosmic::Action::App(msg),
+					);
+				}
+				Task::none()
+			}
+			Message::RulesFetched(result) => match result {
+				Ok(rules) => {
+					let mut policies: Vec<String> = rules.iter().map(|rule| rule.proxy.clone()).collect();
+					policies.sort();
+					policies.dedup();
+					if self.rule_policy.as_ref().is_some_and(|policy| !policies.contains(policy)) {
+						self.rule_policy = None;
+					}
+					self.session.rule_policies = policies;
+					self.session.rules = Some(rules);
+					self.filter_rules();
+					Task::none()
+				}
+				Err(err) => self.update(Message::ApiFailed(err)),
+			},
+			Message::RuleSearchChanged(search) => {
+				self.rule_search = search;
+				self.filter_rules();
+				Task::none()
+			}
+			Message::RulePolicySelected(policy) => {
+				self.rule_policy = policy;
+				self.filter_rules();
+				Task::none()
+			}
+			Message::RefreshProviders => {
+				let Some(api) = self.session.api.clone() else {
+					return Task::none();
+				};
+				let proxy_api = api.clone();
+				let mut tasks = vec![Task::perform(
+					async move { Message::ProxyProvidersFetched(proxy_api.proxy_providers().await) },
+					|msg| cosmic::Action::App(msg),
+				)];
+				if self.supports(CoreFeature::RuleProviders) {
+					tasks.push(Task::perform(
+						async move { Message::RuleProvidersFetched(api.rule_providers().await) },
+						|msg| cosmic::Action::App(msg),
+					));
+				}
+				Task::batch(tasks)
+			}
+			Message::ProxyProvidersFetched(result) => match result {
+				Ok(providers) => {
+					self.session.proxy_providers = Some(providers);
+					Task::none()
+				}
+				Err(err) => self.update(Message::ApiFailed(err)),
+			},
+			Message::RuleProvidersFetched(result) => match result {
+				Ok(providers) => {
+					self.session.rule_providers = Some(providers);
+					Task::none()
+				}
+				Err(err) => self.update(Message::ApiFailed(err)),
+			},
+			Message::UpdateProvider(kind, name) => {
+				let Some(api) = self.session.api.clone() else {
+					return Task::none();
+				};
+				self.session.busy_providers.insert((kind, name.clone()));
+				Task::perform(
+					async move {
+						let result = api.update_provider(kind, &name).await;
+						Message::ProviderActionDone(kind, name, result)
+					},
+					|msg| cosmic::Action::App(msg),
+				)
+			}
+			Message::HealthCheckProvider(name) => {
+				let Some(api) = self.session.api.clone() else {
+					return Task::none();
+				};
+				self.session.busy_providers.insert((ProviderKind::Proxy, name.clone()));
+				Task::perform(
+					async move {
+						let result = api.healthcheck_provider(&name).await;
+						Message::ProviderActionDone(ProviderKind::Proxy, name, result)
+					},
+					|msg| cosmic::Action::App(msg),
+				)
+			}
+			Message::ProviderActionDone(kind, name, result) => {
+				self.session.busy_providers.remove(&(kind, name));
+				if let Err(err) = result {
+					return self.update(Message::ApiFailed(err));
+				}
+				self.update(Message::RefreshProviders)
+			}
+			Message::LogsReceived(entries) => {
+				// Hold lines back while paused so the view stays put
+				let target = if self.logs_paused {
+					&mut self.held_logs
+				} else {
+					&mut self.logs
+				};
+				for entry in entries {
+					if target.len() == LOG_BUFFER_LINES {
+						target.pop_front();
+					}
+					target.push_back(entry);
+				}
+				Task::none()
+			}
+			Message::SetLogLevel(level) => {
+				// The subscription reconnects with the new level
+				self.log_level = level;
+				Task::none()
+			}
+			Message::ToggleLogPause => {
+				self.logs_paused = !self.logs_paused;
+				if !self.logs_paused {
+					let held = std::mem::take(&mut self.held_logs);
+					self.update(Message::LogsReceived(held.into()))
+				} else {
+					Task::none()
+				}
+			}
+			Message::LogFilterChanged(filter) => {
+				self.log_filter = filter;
+				Task::none()
+			}
+			Message::ClearLogs => {
+				self.logs.clear();
+				self.held_logs.clear();
+				self.log_export = None;
+				Task::none()
+			}
+			Message::ExportLogs => {
+				let text: String = self
+					.logs
+					.iter()
+					.map(|entry| format!("[{}] {}\n", entry.level, entry.payload))
+					.collect();
+				Task::perform(
+					async move {
+						let dir = dirs::download_dir()
+							.or_else(dirs::home_dir)
+							.unwrap_or_else(|| PathBuf::from("."));
+						let stamp = std::time::SystemTime::now()
+							.duration_since(std::time::UNIX_EPOCH)
+							.map(|d| d.as_secs())
+							.unwrap_or_default();
+						let path = dir.join(format!("cosmic-clash-{}.log", stamp));
+						let result = tokio::fs::write(&path, text)
+							.await
+							.map(|()| path)
+							.map_err(|err| err.to_string());
+						Message::LogsExported(result)
+					},
+					|msg| cosmic::Action::App(msg),
+				)
+			}
+			Message::LogsExported(result) => {
+				match &result {
+					Ok(path) => tracing::info!("Exported core log to {:?}", path),
+					Err(err) => tracing::warn!("Failed to export core log: {}", err),
+				}
+				self.log_export = Some(result);
+				Task::none()
+			}
+			Message::RuntimeConfigFetched(result) => match result {
+				Ok(config) => {
+					self.session.runtime_config = Some(config);
+					Task::none()
+				}
+				// Mostly the background poll, so it must not mask an action's error
+				Err(err) => self.update(Message::StreamFailed(err)),
+			},
+			Message::SetRunMode(mode) => {
+				let Some(api) = self.session.api.clone() else {
+					return Task::none();
+				};
+				// Show the new mode right away; the next poll corrects it if the core refused
+				if let Some(config) = &mut self.session.runtime_config {
+					config.mode = Some(mode);
+				}
+				let patch = ConfigPatch {
+					mode: Some(mode),
+					..ConfigPatch::default()
+				};
+				Task::perform(
+					async move { Message::RuntimeConfigPatched(api.patch_configs(&patch).await) },
+					|msg| cosmic::Action::App(msg),
+				)
+			}
+			Message::RuntimeConfigPatched(result) => {
+				if let Err(err) = result {
+					return self.update(Message::ApiFailed(err));
+				}
+				let Some(api) = self.session.api.clone() else {
+					return Task::none();
+				};
+				Task::perform(
+					async move { Message::RuntimeConfigFetched(api.get_configs().await) },
+					|msg| cosmic::Action::App(msg),
+				)
+			}
+			Message::ApiFailed(err) => {
+				tracing::warn!("Clash API call failed: {}", err);
+				self.session.api_error = Some(err);
+				Task::none()
+			}
+			Message::StreamFailed(err) => {
+				tracing::warn!("Clash API stream failed: {}", err);
+				self.session.stream_error = Some(err);
 				Task::none()
 			}
 			Message::EditSetting(field) => {
@@ -267,6 +1566,16 @@ impl Application for AppModel {
 					SettingField::ConfigDir => self.config.config_dir().to_string_lossy().to_string(),
 					SettingField::ApiPort => self.config.api_port.to_string(),
 					SettingField::ApiSecret => self.config.api_secret.clone().unwrap_or_default(),
+					SettingField::MaxRestarts => self.config.max_restarts.to_string(),
+					SettingField::StopTimeout => self.config.stop_timeout_secs.to_string(),
+					SettingField::ManifestUrl => self.config.core_manifest_url.clone().unwrap_or_default(),
+					SettingField::DelayTestUrl => self.config.delay_test_url.clone(),
+					SettingField::DelayTimeout => self.config.delay_timeout_ms.to_string(),
+					SettingField::MonthlyQuota => self
+						.config
+						.monthly_quota_gib
+						.map(|gib| gib.to_string())
+						.unwrap_or_default(),
 				};
 				Task::none()
 			}
@@ -275,10 +1584,13 @@ impl Application for AppModel {
 				Task::none()
 			}
 			Message::SaveSetting => {
+				let mut task = Task::none();
 				if let Some(field) = self.editing_setting.take() {
 					match field {
 						SettingField::BinaryPath => {
 							self.config.clash_binary_path = Some(self.edit_value.clone());
+							self.core_info = None;
+							task = self.inspect_core();
 						}
 						SettingField::ConfigDir => {
 							self.config.config_dir = Some(self.edit_value.clone());
@@ -294,35 +1606,95 @@ impl Application for AppModel {
 							} else {
 								Some(self.edit_value.clone())
 							};
+							if self.existing_core.is_some() {
+								task = self.detect_existing_core();
+							}
+						}
+						SettingField::MaxRestarts => {
+							if let Ok(max_restarts) = self.edit_value.parse() {
+								self.config.max_restarts = max_restarts;
+							}
+						}
+						SettingField::StopTimeout => {
+							if let Ok(secs) = self.edit_value.parse() {
+								self.config.stop_timeout_secs = secs;
+							}
+						}
+						SettingField::ManifestUrl => {
+							self.config.core_manifest_url = if self.edit_value.is_empty() {
+								None
+							} else {
+								Some(self.edit_value.clone())
+							};
+						}
+						SettingField::DelayTestUrl => {
+							if !self.edit_value.trim().is_empty() {
+								self.config.delay_test_url = self.edit_value.trim().to_string();
+							}
+						}
+						SettingField::DelayTimeout => {
+							if let Ok(timeout) = self.edit_value.parse() {
+								self.config.delay_timeout_ms = timeout;
+							}
+						}
+						SettingField::MonthlyQuota => {
+							let value = self.edit_value.trim();
+							if value.is_empty() {
+								self.config.monthly_quota_gib = None;
+							} else if let Ok(gib) = value.parse() {
+								self.config.monthly_quota_gib = Some(gib);
+							}
 						}
 					}
 					let _ = self.config.save();
 					self.edit_value.clear();
 				}
-				Task::none()
+				task
 			}
 			Message::CancelEdit => {
 				self.editing_setting = None;
 				self.edit_value.clear();
 				Task::none()
 			}
+			Message::Quit => {
+				self.flush_usage();
+				// Let the core restore TUN/DNS before the process goes away,
+				// including one whose stop is already under way
+				let pending = self.stopping_core.take();
+				let sidecar = self.sidecar.take();
+				Task::perform(
+					async move {
+						if let Some(pending) = pending {
+							pending.await;
+						}
+						if let Some(sidecar) = sidecar {
+							sidecar.shutdown().await;
+						}
+						Message::Exit
+					},
+					|msg| cosmic::Action::App(msg),
+				)
+			}
+			Message::Exit => cosmic::iced::exit(),
 			Message::Nop => Task::none(),
 		}
 	}
 
+	fn on_close_requested(&self, _id: cosmic::iced::window::Id) -> Option<Self::Message> {
+		Some(Message::Quit)
+	}
+
 	fn nav_model(&self) -> Option<&nav_bar::Model> {
 		Some(&self.nav)
 	}
 
 	fn on_nav_select(&mut self, id: nav_bar::Id) -> Task<Self::Message> {
 		self.nav.activate(id);
-		self.context_page = match self.nav.position(id) {
-			Some(0) => ContextPage::Home,
-			Some(1) => ContextPage::Profile,
-			Some(2) => ContextPage::Settings,
-			_ => return Task::none(),
+		let Some(page) = self.nav.data::<ContextPage>(id).copied() else {
+			return Task::none();
 		};
-		self.update_title()
+		self.context_page = page;
+		Task::batch(vec![self.update_title(), self.on_page_shown()])
 	}
 
 	fn style(&self) -> Option<cosmic::iced::core::theme::Style> {
@@ -340,6 +1712,12 @@ impl Application for AppModel {
 
 		let content: Element<'_, Self::Message> = match self.context_page {
 			ContextPage::Home => crate::pages::home::view_home(self, space_s),
+			ContextPage::Proxies => crate::pages::proxies::view_proxies(self, space_s),
+			ContextPage::Connections => crate::pages::connections::view_connections(self, space_s),
+			ContextPage::Rules => crate::pages::rules::view_rules(self, space_s),
+			ContextPage::Providers => crate::pages::providers::view_providers(self, space_s),
+			ContextPage::Logs => crate::pages::logs::view_logs(self, space_s),
+			ContextPage::Usage => crate::pages::usage::view_usage(self, space_s),
 			ContextPage::Profile => crate::pages::profile::view_profile(self, space_s),
 			ContextPage::Settings => crate::pages::settings::view_settings(self, space_s),
 		};
@@ -353,12 +1731,48 @@ impl Application for AppModel {
 	}
 
 	fn subscription(&self) -> Subscription<Self::Message> {
-		// Update traffic every 2 seconds when VPN is active
-		if self.vpn_is_active {
-			cosmic::iced::time::every(Duration::from_secs(2)).map(|_| Message::UpdateTraffic)
-		} else {
-			Subscription::none()
+		let mut subscriptions = Vec::with_capacity(8);
+
+		// Shut the core down cleanly on SIGINT/SIGTERM
+		subscriptions.push(Subscription::run(termination_signals));
+
+		// Stream traffic from the controller while the VPN is active
+		if let (true, Some(api)) = (self.vpn_is_active, &self.session.api) {
+			subscriptions.push(Subscription::run_with(api.clone(), crate::subscriptions::traffic));
+			subscriptions.push(Subscription::run_with(api.clone(), crate::subscriptions::runtime_config));
+
+			if self.supports(CoreFeature::Memory) {
+				subscriptions.push(Subscription::run_with(api.clone(), crate::subscriptions::memory));
+			}
+
+			// Connections change constantly, so only poll them while the table
+			// or the dashboard totals are on screen
+			if matches!(self.context_page, ContextPage::Home | ContextPage::Connections) {
+				subscriptions.push(Subscription::run_with(api.clone(), crate::subscriptions::connections));
+			}
+
+			// Collect the log in the background so it has history when opened,
+			// and keep it running while paused so no lines are lost
+			subscriptions.push(Subscription::run_with(
+				(api.clone(), self.log_level),
+				crate::subscriptions::logs,
+			));
 		}
+
+		// Keep subscription profiles fresh whether or not the core runs
+		if self.profiles.has_scheduled_refresh() {
+			subscriptions.push(Subscription::run(crate::subscriptions::profile_refresh));
+		}
+
+		// Follow the supervisor so crashes and restarts reach the UI
+		if let Some(sidecar) = &self.sidecar {
+			subscriptions.push(
+				Subscription::run_with(sidecar.watch_status(), sidecar::status_stream)
+					.map(Message::SidecarStatusChanged),
+			);
+		}
+
+		Subscription::batch(subscriptions)
 	}
 }
 
@@ -368,29 +1782,243 @@ impl AppModel {
 		Task::none()
 	}
 
+	/// Switches to `page` as if it had been picked in the nav bar.
+	pub fn show_page(&mut self, page: ContextPage) -> Task<Message> {
+		let id = self
+			.nav
+			.iter()
+			.find(|id| self.nav.data::<ContextPage>(*id) == Some(&page));
+		if let Some(id) = id {
+			self.nav.activate(id);
+		}
+		self.context_page = page;
+		Task::batch(vec![self.update_title(), self.on_page_shown()])
+	}
+
+	/// Refreshes whatever the current page shows from the controller.
+	fn on_page_shown(&mut self) -> Task<Message> {
+		match self.context_page {
+			ContextPage::Proxies => self.update(Message::RefreshProxies),
+			ContextPage::Rules => self.update(Message::RefreshRules),
+			ContextPage::Providers => self.update(Message::RefreshProviders),
+			_ => Task::none(),
+		}
+	}
+
+	/// Writes counted traffic to disk so it survives a crash or restart.
+	fn flush_usage(&mut self) {
+		self.usage_flushed_at = Instant::now();
+		if let Err(err) = self.usage.save() {
+			tracing::warn!("Failed to save traffic usage: {}", err);
+		}
+	}
+
+	/// Asks the configured binary what core it is.
+	pub fn inspect_core(&self) -> Task<Message> {
+		let binary = self.config.clash_binary();
+		Task::perform(
+			async move { Message::CoreInspected(core_info::inspect(&binary).await) },
+			|msg| cosmic::Action::App(msg),
+		)
+	}
+
+	/// Whether the configured core implements `feature`. Cores we could not
+	/// identify are assumed to support everything.
+	pub fn supports(&self, feature: CoreFeature) -> bool {
+		match &self.core_info {
+			Some(Ok(info)) => info.supports(feature),
+			_ => true,
+		}
+	}
+
+	/// Command-line dialect of the configured core. Until it has been
+	/// inspected we assume clash-rs.
+	fn core_flavour(&self) -> CoreFlavour {
+		match &self.core_info {
+			Some(Ok(info)) => info.flavour,
+			_ => CoreFlavour::ClashRs,
+		}
+	}
+
+	/// Members of proxy group `group` in the last `/proxies` snapshot.
+	fn group_members(&self, group: &str) -> Vec<String> {
+		self.session.proxies
+			.as_ref()
+			.and_then(|p| p.proxies.get(group))
+			.and_then(|g| g.all.clone())
+			.unwrap_or_default()
+	}
+
+	/// Tests `names` individually, reporting each result as it arrives.
+	fn test_delays(&self, api: ClashApi, names: Vec<String>) -> Task<Message> {
+		let stream = api.delay_stream(names, self.config.delay_test(), DELAY_TEST_CONCURRENCY);
+		Task::run(stream, |(name, result)| cosmic::Action::App(Message::DelayTested(name, result)))
+	}
+
+	/// Looks for a core left running by a previous session, either through
+	/// the pidfile or by probing the controller port.
+	pub fn detect_existing_core(&self) -> Task<Message> {
+		let api = ClashApi::new(self.config.api_url(), self.config.api_secret.clone());
+		let binary = self.config.clash_binary();
+		let work_dir = self.config.config_dir();
+		Task::perform(
+			async move {
+				let pid = sidecar::running_pid(&work_dir, &binary);
+				// A core behind another secret still holds the port
+				let (version, unauthorized) = match api.version().await {
+					Ok(v) => (Some(v.version.unwrap_or_default()), false),
+					Err(ClashApiError::Unauthorized) => (None, true),
+					Err(_) => (None, false),
+				};
+				let existing = (pid.is_some() || version.is_some() || unauthorized).then_some(ExistingCore {
+					pid,
+					version,
+					unauthorized,
+				});
+				Message::ExistingCoreDetected(existing)
+			},
+			|msg| cosmic::Action::App(msg),
+		)
+	}
+
+	/// Merges the override onto the profile at `path` and checks the result
+	/// with the core's config-test mode before it is activated.
+	fn validate_config(&self, activation: Activation, path: PathBuf) -> Task<Message> {
+		let binary = self.config.clash_binary();
+		let flavour = self.core_flavour();
+		let work_dir = self.config.config_dir();
+		let controller = self.config.controller();
+		Task::perform(
+			async move {
+				let result = overrides::stage(&binary, flavour, &work_dir, &path, &overrides::path(), &controller).await;
+				Message::ConfigValidated(activation, result)
+			},
+			|msg| cosmic::Action::App(msg),
+		)
+	}
+
+	/// Shows the core as stopping until `stopping` resolves, so nothing can
+	/// start a new core while the old one still holds the controller port.
+	fn wait_for_stop(&mut self, stopping: sidecar::Stopping, failure: Option<ReadinessError>) -> Task<Message> {
+		self.readiness = Readiness::Stopping;
+		self.stopping_core = Some(stopping.clone());
+		Task::perform(
+			async move {
+				stopping.await;
+				Message::CoreStopped(failure)
+			},
+			|msg| cosmic::Action::App(msg),
+		)
+	}
+
+	/// Spawns the sidecar and waits for its controller to come up.
+	fn start_sidecar(&mut self) -> Task<Message> {
+		let mut sidecar = build_sidecar(&self.config).with_flavour(self.core_flavour());
+		if let Err(err) = sidecar.start() {
+			self.readiness = Readiness::Failed(ReadinessError::Spawn(err.to_string()));
+			return Task::none();
+		}
+		self.sidecar_status = sidecar.status();
+
+		// Only flip the toggle once the controller answers
+		let api = ClashApi::new(self.config.api_url(), self.config.api_secret.clone());
+		let status = sidecar.watch_status().receiver;
+		self.sidecar = Some(sidecar);
+		Task::perform(
+			async move {
+				match readiness::wait_until_ready(api, status, readiness::READY_TIMEOUT).await {
+					Ok(v) => Message::CoreReady(v.version.unwrap_or_default()),
+					Err(err) => Message::CoreFailed(err),
+				}
+			},
+			|msg| cosmic::Action::App(msg),
+		)
+	}
+
+	fn filter_rules(&mut self) {
+		self.session.rule_matches = match &self.session.rules {
+			Some(all) => rules::filter(all, &self.rule_search, self.rule_policy.as_deref()),
+			None => RuleMatches::default(),
+		};
+	}
+
+	/// The controller error to show: a failed action, else a failing stream.
+	pub fn controller_error(&self) -> Option<&ClashApiError> {
+		self.session.api_error.as_ref().or(self.session.stream_error.as_ref())
+	}
+
+	/// Latest lines printed by the clash core, oldest first.
+	pub fn core_output(&self, count: usize) -> Vec<sidecar::OutputLine> {
+		self.sidecar
+			.as_ref()
+			.map(|sidecar| sidecar.output().tail(count))
+			.unwrap_or_default()
+	}
+
+	/// Stamps the active profile as used now.
+	fn mark_profile_used(&mut self) {
+		let Some(file) = self.config.active_profile.as_deref() else {
+			return;
+		};
+		if let Some(profile) = self.profiles.get_mut(file) {
+			profile.last_used_at = Some(profiles::unix_now());
+			self.save_profiles();
+		}
+	}
+
+	fn save_profiles(&self) {
+		if let Err(err) = self.profiles.save() {
+			tracing::warn!("Failed to save the profile index: {}", err);
+		}
+	}
+
 	/// Scan for config profiles.
 	pub fn scan_profiles(&mut self) -> Task<Message> {
 		let config_dir = self.config.config_dir();
 		Task::perform(
 			async move {
-				let mut profiles = Vec::new();
-				if let Ok(entries) = std::fs::read_dir(config_dir) {
-					for entry in entries.flatten() {
-						let path = entry.path();
-						if path.extension().map_or(false, |ext| ext == "yaml" || ext == "yml") {
-							if let Some(name) = path.file_stem().and_then(|n| n.to_str()) {
-								profiles.push(name.to_string());
-							}
-						}
-					}
-				}
-				Message::ProfileScanResult(profiles)
+				Message::ProfileScanResult(profiles::scan(&config_dir).map_err(|err| err.to_string()))
 			},
 			|msg| cosmic::Action::App(msg),
 		)
 	}
 }
 
+/// Builds a sidecar manager from the persisted configuration.
+fn build_sidecar(config: &Config) -> SidecarManager {
+	SidecarManager::new(
+		config.clash_binary(),
+		config.config_dir(),
+		config.runtime_path(),
+	)
+	.with_restart_policy(config.restart_policy())
+	.with_grace_period(config.stop_grace_period())
+}
+
+/// Yields `Message::Quit` whenever the process is asked to terminate.
+fn termination_signals() -> impl futures_util::Stream<Item = Message> {
+	futures_util::stream::unfold((), |()| async {
+		#[cfg(unix)]
+		{
+			use tokio::signal::unix::{SignalKind, signal};
+			match (signal(SignalKind::interrupt()), signal(SignalKind::terminate())) {
+				(Ok(mut interrupt), Ok(mut terminate)) => {
+					tokio::select! {
+						_ = interrupt.recv() => {}
+						_ = terminate.recv() => {}
+					}
+				}
+				_ => std::future::pending::<()>().await,
+			}
+		}
+		#[cfg(not(unix))]
+		if tokio::signal::ctrl_c().await.is_err() {
+			std::future::pending::<()>().await;
+		}
+		Some((Message::Quit, ()))
+	})
+}
+
 // Menu types (simplified)
 #[derive(Debug, Clone)]
 pub enum MenuAction {
@@ -1,16 +1,38 @@
 // SPDX-License-Identifier: AGPL3.0
 
-use std::path::PathBuf;
+use std::{path::PathBuf, time::Duration};
 
 use serde::{Deserialize, Serialize};
 
+use crate::{
+	api::DelayTest,
+	overrides::{self, Controller},
+	profiles,
+	sidecar::RestartPolicy,
+};
+
 #[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Config {
 	pub clash_binary_path: Option<String>,
 	pub config_dir: Option<String>,
 	pub active_profile: Option<String>,
 	pub api_port: u16,
 	pub api_secret: Option<String>,
+	/// How many times a crashed core is restarted before giving up.
+	pub max_restarts: u32,
+	/// Seconds the core gets to exit after SIGTERM before it is killed.
+	pub stop_timeout_secs: u64,
+	/// JSON release index used by the managed core installer.
+	pub core_manifest_url: Option<String>,
+	/// Binary that was active before the last managed install, for rollback.
+	pub previous_clash_binary_path: Option<String>,
+	/// URL fetched through a proxy to measure its latency.
+	pub delay_test_url: String,
+	/// Milliseconds after which a latency test counts as failed.
+	pub delay_timeout_ms: u32,
+	/// Monthly traffic allowance in GiB; the usage page warns when it runs low.
+	pub monthly_quota_gib: Option<u64>,
 }
 
 impl Default for Config {
@@ -21,6 +43,13 @@ impl Default for Config {
 			active_profile: None,
 			api_port: 9090,
 			api_secret: None,
+			max_restarts: 5,
+			stop_timeout_secs: 5,
+			core_manifest_url: None,
+			previous_clash_binary_path: None,
+			delay_test_url: "https://www.gstatic.com/generate_204".to_string(),
+			delay_timeout_ms: 5000,
+			monthly_quota_gib: None,
 		}
 	}
 }
@@ -33,6 +62,29 @@ impl Config {
 			.unwrap_or_else(|| dirs::config_dir().unwrap_or_else(|| PathBuf::from(".")).join("clash"))
 	}
 
+	/// Absolute path of the config the core should run, see [`profiles::resolve`].
+	pub fn active_profile_path(&self) -> PathBuf {
+		self.profile_path(self.active_profile.as_deref())
+	}
+
+	/// Absolute path of `profile`, or of the default config for `None`.
+	pub fn profile_path(&self, profile: Option<&str>) -> PathBuf {
+		profiles::resolve(&self.config_dir(), profile)
+	}
+
+	/// Absolute path of the merged config the core is launched with.
+	pub fn runtime_path(&self) -> PathBuf {
+		overrides::runtime_path(&self.config_dir())
+	}
+
+	/// Controller settings forced into the runtime config, matching [`Self::api_url`].
+	pub fn controller(&self) -> Controller {
+		Controller {
+			address: format!("127.0.0.1:{}", self.api_port),
+			secret: self.api_secret.clone(),
+		}
+	}
+
 	pub fn clash_binary(&self) -> PathBuf {
 		self.clash_binary_path
 			.as_ref()
@@ -44,6 +96,52 @@ impl Config {
 		format!("http://127.0.0.1:{}", self.api_port)
 	}
 
+	pub fn restart_policy(&self) -> RestartPolicy {
+		RestartPolicy {
+			max_restarts: self.max_restarts,
+			..RestartPolicy::default()
+		}
+	}
+
+	pub fn stop_grace_period(&self) -> Duration {
+		Duration::from_secs(self.stop_timeout_secs)
+	}
+
+	pub fn delay_test(&self) -> DelayTest {
+		DelayTest {
+			url: self.delay_test_url.clone(),
+			timeout_ms: self.delay_timeout_ms,
+		}
+	}
+
+	pub fn monthly_quota_bytes(&self) -> Option<u64> {
+		self.monthly_quota_gib
+			.filter(|gib| *gib > 0)
+			.map(|gib| gib.saturating_mul(1024 * 1024 * 1024))
+	}
+
+	/// Points the config at a newly installed core, remembering the old one.
+	/// An auto-detected core is remembered by its path, so it can be rolled
+	/// back to as well.
+	pub fn switch_binary(&mut self, binary: PathBuf) {
+		let current = self.clash_binary();
+		if current != binary {
+			self.previous_clash_binary_path = Some(current.to_string_lossy().to_string());
+		}
+		self.clash_binary_path = Some(binary.to_string_lossy().to_string());
+	}
+
+	/// Swaps back to the core that was active before the last switch.
+	pub fn rollback_binary(&mut self) -> bool {
+		match self.previous_clash_binary_path.take() {
+			Some(previous) => {
+				self.previous_clash_binary_path = self.clash_binary_path.replace(previous);
+				true
+			}
+			None => false,
+		}
+	}
+
 	fn config_path() -> PathBuf {
 		dirs::config_dir()
 			.unwrap_or_else(|| PathBuf::from("."))
@@ -57,7 +155,10 @@ impl Config {
 			std::fs::create_dir_all(parent)?;
 		}
 		let json = serde_json::to_string_pretty(self)?;
-		std::fs::write(path, json)?;
+		// Write next to the target and rename, so a crash never leaves a torn file
+		let tmp = path.with_extension("json.tmp");
+		std::fs::write(&tmp, json)?;
+		std::fs::rename(tmp, path)?;
 		Ok(())
 	}
 
@@ -73,19 +174,106 @@ impl Config {
 }
 
 fn detect_default_binary() -> PathBuf {
-	// Try to find clash binary in common locations
-	let candidates = [
-		"/usr/local/bin/clash",
-		"/usr/bin/clash",
-		"clash", // Try PATH
-	];
-
-	for candidate in &candidates {
-		if std::path::Path::new(candidate).exists() {
-			return PathBuf::from(candidate);
+	// Try to find a clash core in common locations, then on PATH
+	let names = ["clash", "clash-rs", "mihomo"];
+	let path = std::env::var_os("PATH").unwrap_or_default();
+	let candidates = ["/usr/local/bin", "/usr/bin"]
+		.into_iter()
+		.map(PathBuf::from)
+		.chain(std::env::split_paths(&path));
+
+	for dir in candidates {
+		for name in &names {
+			let candidate = dir.join(name);
+			if candidate.is_file() {
+				return candidate;
+			}
 		}
 	}
 
 	// Fallback
 	PathBuf::from("clash")
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn switch_binary_remembers_a_detected_core() {
+		let mut config = Config::default();
+		let detected = config.clash_binary();
+		config.switch_binary(PathBuf::from("/opt/cores/0.7.1/clash"));
+		assert_eq!(config.previous_clash_binary_path, Some(detected.to_string_lossy().to_string()));
+
+		assert!(config.rollback_binary());
+		assert_eq!(config.clash_binary(), detected);
+		assert_eq!(config.previous_clash_binary_path.as_deref(), Some("/opt/cores/0.7.1/clash"));
+	}
+
+	fn in_dir(dir: &std::path::Path, active_profile: Option<&str>) -> Config {
+		Config {
+			config_dir: Some(dir.to_string_lossy().to_string()),
+			active_profile: active_profile.map(str::to_string),
+			..Config::default()
+		}
+	}
+
+	#[test]
+	fn no_profile_runs_the_default_config() {
+		let dir = tempfile::tempdir().unwrap();
+		let config = in_dir(dir.path(), None);
+		assert_eq!(config.active_profile_path(), dir.path().join("config.yaml"));
+		assert_eq!(config.profile_path(None), config.active_profile_path());
+	}
+
+	#[test]
+	fn profiles_fall_back_to_yml() {
+		let dir = tempfile::tempdir().unwrap();
+		std::fs::write(dir.path().join("work.yml"), "").unwrap();
+		let config = in_dir(dir.path(), Some("work"));
+		assert_eq!(config.active_profile_path(), dir.path().join("work.yml"));
+
+		// The .yaml wins once both exist
+		std::fs::write(dir.path().join("work.yaml"), "").unwrap();
+		assert_eq!(config.active_profile_path(), dir.path().join("work.yaml"));
+	}
+
+	#[test]
+	fn start_select_and_reload_agree_on_the_paths() {
+		let dir = tempfile::tempdir().unwrap();
+		std::fs::write(dir.path().join("work.yaml"), "").unwrap();
+		let before = in_dir(dir.path(), None);
+		let after = in_dir(dir.path(), Some("work"));
+
+		// Selecting checks the file that reloading and restarting then use
+		assert_eq!(before.profile_path(Some("work")), after.active_profile_path());
+		// The sidecar and every reload hand the core the same runtime config
+		assert_eq!(before.runtime_path(), after.runtime_path());
+		assert_eq!(after.runtime_path(), dir.path().join("cosmic-clash-runtime.yaml"));
+	}
+
+	#[test]
+	fn a_relative_config_dir_gives_absolute_paths() {
+		let config = Config {
+			config_dir: Some("clash-profiles".to_string()),
+			active_profile: Some("work".to_string()),
+			..Config::default()
+		};
+		for path in [config.active_profile_path(), config.profile_path(None), config.runtime_path()] {
+			assert!(path.is_absolute(), "{}", path.display());
+			assert!(path.parent().unwrap().ends_with("clash-profiles"), "{}", path.display());
+		}
+	}
+
+	#[test]
+	fn reinstalling_the_same_binary_keeps_the_rollback_target() {
+		let mut config = Config {
+			clash_binary_path: Some("/usr/bin/clash".to_string()),
+			..Config::default()
+		};
+		config.switch_binary(PathBuf::from("/opt/cores/0.7.1/clash"));
+		config.switch_binary(PathBuf::from("/opt/cores/0.7.1/clash"));
+		assert_eq!(config.previous_clash_binary_path.as_deref(), Some("/usr/bin/clash"));
+	}
+}
@@ -0,0 +1,226 @@
+// SPDX-License-Identifier: AGPL3.0
+
+use std::{ffi::OsStr, path::Path, process::Stdio, time::Duration};
+
+use semver::Version;
+use tokio::process::Command;
+
+/// How long `<binary> -v` may take before we give up on it.
+const INSPECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Oldest clash-rs release the GUI is tested against.
+pub const MIN_CLASH_RS: Version = Version::new(0, 7, 0);
+/// Oldest mihomo (clash-meta) release the GUI is tested against.
+pub const MIN_MIHOMO: Version = Version::new(1, 18, 0);
+
+/// Which clash implementation a binary is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CoreFlavour {
+	ClashRs,
+	Mihomo,
+	Unknown,
+}
+
+impl std::fmt::Display for CoreFlavour {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::ClashRs => write!(f, "clash-rs"),
+			Self::Mihomo => write!(f, "mihomo"),
+			Self::Unknown => write!(f, "clash"),
+		}
+	}
+}
+
+impl CoreFlavour {
+	/// Arguments pointing the core at its working directory and config file.
+	/// clash-rs takes the config with `-c`; mihomo and the Go cores use `-f`.
+	pub fn config_args<'a>(self, work_dir: &'a Path, config: &'a Path) -> [&'a OsStr; 4] {
+		let config_flag = match self {
+			Self::ClashRs => "-c",
+			Self::Mihomo | Self::Unknown => "-f",
+		};
+		["-d".as_ref(), work_dir.as_os_str(), config_flag.as_ref(), config.as_os_str()]
+	}
+}
+
+/// Controller features that not every core implements.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CoreFeature {
+	/// `GET /group/{name}/delay`
+	GroupDelay,
+	/// `GET /memory`
+	Memory,
+	/// `GET /providers/rules`
+	RuleProviders,
+}
+
+/// What `<binary> -v` told us about a core.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CoreInfo {
+	pub flavour: CoreFlavour,
+	pub version: Option<Version>,
+	/// First line of the version output, for display.
+	pub raw: String,
+}
+
+impl CoreInfo {
+	/// Minimum version for this flavour, if we know one.
+	pub fn minimum_version(&self) -> Option<Version> {
+		match self.flavour {
+			CoreFlavour::ClashRs => Some(MIN_CLASH_RS),
+			CoreFlavour::Mihomo => Some(MIN_MIHOMO),
+			CoreFlavour::Unknown => None,
+		}
+	}
+
+	/// False when the version is known to be older than the supported minimum.
+	pub fn is_supported(&self) -> bool {
+		match (&self.version, self.minimum_version()) {
+			(Some(version), Some(minimum)) => *version >= minimum,
+			_ => true,
+		}
+	}
+
+	/// Unknown cores get the benefit of the doubt.
+	pub fn supports(&self, feature: CoreFeature) -> bool {
+		!matches!((self.flavour, feature), (CoreFlavour::ClashRs, CoreFeature::RuleProviders))
+	}
+}
+
+/// Runs `binary -v` (falling back to `--version`) and parses the result.
+pub async fn inspect(binary: &Path) -> Result<CoreInfo, String> {
+	let mut last_error = String::new();
+	for flag in ["-v", "--version"] {
+		match run_version(binary, flag).await {
+			Ok(output) => match parse_version_output(&output) {
+				Some(info) => return Ok(info),
+				None => last_error = format!("{} does not look like a clash core", binary.display()),
+			},
+			Err(err) => last_error = err,
+		}
+	}
+	Err(last_error)
+}
+
+async fn run_version(binary: &Path, flag: &str) -> Result<String, String> {
+	let mut cmd = Command::new(binary);
+	cmd.arg(flag).stdin(Stdio::null()).kill_on_drop(true);
+
+	let output = match tokio::time::timeout(INSPECT_TIMEOUT, cmd.output()).await {
+		Ok(Ok(output)) => output,
+		Ok(Err(err)) => return Err(format!("failed to run {}: {}", binary.display(), err)),
+		Err(_) => return Err(format!("{} {} timed out", binary.display(), flag)),
+	};
+	if !output.status.success() {
+		return Err(format!("{} {} exited with {}", binary.display(), flag, output.status));
+	}
+	let mut text = String::from_utf8_lossy(&output.stdout).into_owned();
+	text.push_str(&String::from_utf8_lossy(&output.stderr));
+	Ok(text)
+}
+
+/// Understands e.g. `clash 0.7.1`, `Mihomo Meta v1.18.1 linux amd64 ...`
+/// and `Clash Meta v1.14.0 ...`.
+fn parse_version_output(output: &str) -> Option<CoreInfo> {
+	let raw = output.lines().map(str::trim).find(|l| !l.is_empty())?.to_string();
+	let lower = raw.to_ascii_lowercase();
+
+	let flavour = if lower.contains("mihomo") || lower.contains("meta") {
+		CoreFlavour::Mihomo
+	} else if lower.contains("clash-rs") || lower.contains("clash_rs") || lower.contains("clash-lib") {
+		CoreFlavour::ClashRs
+	} else if lower.contains("clash") {
+		// The Go cores mention their toolchain; clash-rs prints just `clash x.y.z`
+		if lower.contains("go1.") {
+			CoreFlavour::Unknown
+		} else {
+			CoreFlavour::ClashRs
+		}
+	} else {
+		return None;
+	};
+
+	let version = raw.split_whitespace().find_map(parse_version_token);
+	Some(CoreInfo { flavour, version, raw })
+}
+
+fn parse_version_token(token: &str) -> Option<Version> {
+	let token = token.trim_start_matches(['v', 'V']);
+	if !token.starts_with(|c: char| c.is_ascii_digit()) {
+		return None;
+	}
+	Version::parse(token).ok().or_else(|| {
+		// Some builds print only `major.minor`
+		let mut parts = token.split('.');
+		let major = parts.next()?.parse().ok()?;
+		let minor = parts.next()?.parse().ok()?;
+		parts.next().is_none().then(|| Version::new(major, minor, 0))
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn parses_clash_rs() {
+		let info = parse_version_output("clash 0.7.1\n").unwrap();
+		assert_eq!(info.flavour, CoreFlavour::ClashRs);
+		assert_eq!(info.version, Some(Version::new(0, 7, 1)));
+		assert_eq!(info.raw, "clash 0.7.1");
+	}
+
+	#[test]
+	fn parses_mihomo() {
+		let info =
+			parse_version_output("Mihomo Meta v1.18.1 linux amd64 with go1.22.0 Mon Feb  5 2024\nUse tags: with_gvisor")
+				.unwrap();
+		assert_eq!(info.flavour, CoreFlavour::Mihomo);
+		assert_eq!(info.version, Some(Version::new(1, 18, 1)));
+	}
+
+	#[test]
+	fn go_clash_is_unknown() {
+		let info = parse_version_output("Clash v1.18.0 linux amd64 with go1.21.4").unwrap();
+		assert_eq!(info.flavour, CoreFlavour::Unknown);
+		assert!(info.is_supported());
+	}
+
+	#[test]
+	fn accepts_major_minor_versions() {
+		let info = parse_version_output("\n  clash-rs v0.8\n").unwrap();
+		assert_eq!(info.version, Some(Version::new(0, 8, 0)));
+	}
+
+	#[test]
+	fn config_flag_follows_the_flavour() {
+		let (dir, config) = (Path::new("/run/clash"), Path::new("/run/clash/config.yaml"));
+		let args = |flavour: CoreFlavour| flavour.config_args(dir, config).map(|arg| arg.to_str().unwrap());
+		assert_eq!(args(CoreFlavour::ClashRs), ["-d", "/run/clash", "-c", "/run/clash/config.yaml"]);
+		assert_eq!(args(CoreFlavour::Mihomo), ["-d", "/run/clash", "-f", "/run/clash/config.yaml"]);
+		assert_eq!(args(CoreFlavour::Unknown), ["-d", "/run/clash", "-f", "/run/clash/config.yaml"]);
+	}
+
+	#[test]
+	fn rejects_other_binaries() {
+		assert_eq!(parse_version_output("Python 3.12.1"), None);
+		assert_eq!(parse_version_output("   \n"), None);
+	}
+
+	#[test]
+	fn old_versions_are_not_supported() {
+		assert!(!parse_version_output("clash 0.6.9").unwrap().is_supported());
+		assert!(!parse_version_output("Mihomo Meta v1.17.0").unwrap().is_supported());
+		assert!(parse_version_output("clash").unwrap().is_supported());
+	}
+
+	#[test]
+	fn clash_rs_lacks_rule_providers() {
+		let clash_rs = parse_version_output("clash 0.7.1").unwrap();
+		assert!(!clash_rs.supports(CoreFeature::RuleProviders));
+		assert!(clash_rs.supports(CoreFeature::GroupDelay));
+		let mihomo = parse_version_output("Mihomo Meta v1.18.1").unwrap();
+		assert!(mihomo.supports(CoreFeature::RuleProviders));
+		assert!(mihomo.supports(CoreFeature::Memory));
+	}
+}
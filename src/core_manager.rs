@@ -0,0 +1,394 @@
+// SPDX-License-Identifier: AGPL3.0
+
+use std::{
+	io::Read,
+	path::{Path, PathBuf},
+	time::Duration,
+};
+
+use semver::Version;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+
+/// Downloads of a core archive may take a while on slow links.
+const DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// JSON index of core releases, e.g.
+///
+/// ```json
+/// { "releases": [ { "version": "0.7.1", "assets": [
+///     { "target": "x86_64-unknown-linux-gnu", "url": "https://…/clash.tar.gz",
+///       "sha256": "…", "format": "tar.gz", "binary": "clash" } ] } ] }
+/// ```
+#[derive(Debug, Clone, Deserialize, Default)]
+pub struct ReleaseManifest {
+	pub releases: Vec<Release>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Release {
+	pub version: String,
+	pub assets: Vec<ReleaseAsset>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct ReleaseAsset {
+	/// Rust target triple the asset was built for.
+	pub target: String,
+	pub url: String,
+	/// Hex encoded SHA-256 of the downloaded file.
+	pub sha256: String,
+	#[serde(default)]
+	pub format: ArchiveFormat,
+	/// Name of the executable inside an archive.
+	#[serde(default = "default_binary_name")]
+	pub binary: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Default)]
+pub enum ArchiveFormat {
+	#[serde(rename = "tar.gz")]
+	TarGz,
+	#[serde(rename = "gz")]
+	Gz,
+	#[default]
+	#[serde(rename = "raw")]
+	Raw,
+}
+
+fn default_binary_name() -> String {
+	"clash".to_string()
+}
+
+/// The newest release that ships an asset for this host.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CoreUpdate {
+	pub version: Version,
+	pub asset: ReleaseAsset,
+}
+
+/// A core unpacked into the data dir.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InstalledCore {
+	pub version: Version,
+	pub binary_path: PathBuf,
+}
+
+/// Target triple of the running build, as used in the manifest.
+pub fn host_target() -> String {
+	let os = match std::env::consts::OS {
+		"linux" if cfg!(target_env = "musl") => "unknown-linux-musl",
+		"linux" => "unknown-linux-gnu",
+		"macos" => "apple-darwin",
+		"windows" => "pc-windows-msvc",
+		other => other,
+	};
+	format!("{}-{}", std::env::consts::ARCH, os)
+}
+
+/// Where managed cores are unpacked, one directory per version.
+pub fn cores_dir() -> PathBuf {
+	dirs::data_dir()
+		.unwrap_or_else(|| PathBuf::from("."))
+		.join("cosmic-clash")
+		.join("cores")
+}
+
+pub async fn fetch_manifest(client: &reqwest::Client, url: &str) -> eyre::Result<ReleaseManifest> {
+	let resp = client.get(url).send().await?.error_for_status()?;
+	Ok(resp.json().await?)
+}
+
+/// Picks the highest version in `manifest` with an asset for `target`.
+pub fn latest_for(manifest: &ReleaseManifest, target: &str) -> Option<CoreUpdate> {
+	manifest
+		.releases
+		.iter()
+		.filter_map(|release| {
+			let version = Version::parse(release.version.trim_start_matches('v')).ok()?;
+			let asset = release.assets.iter().find(|a| a.target == target)?;
+			Some(CoreUpdate {
+				version,
+				asset: asset.clone(),
+			})
+		})
+		.max_by(|a, b| a.version.cmp(&b.version))
+}
+
+/// Downloads `update`, checks its digest and unpacks it under `cores_dir`.
+///
+/// The release is staged in a scratch directory and renamed into place, so a
+/// half-finished install never shows up as a usable version.
+pub async fn install(client: &reqwest::Client, update: &CoreUpdate, cores_dir: &Path) -> eyre::Result<InstalledCore> {
+	tracing::info!("Downloading clash core {} from {}", update.version, update.asset.url);
+	let bytes = client
+		.get(&update.asset.url)
+		.timeout(DOWNLOAD_TIMEOUT)
+		.send()
+		.await?
+		.error_for_status()?
+		.bytes()
+		.await?;
+
+	let digest = format!("{:x}", Sha256::digest(&bytes));
+	if !digest.eq_ignore_ascii_case(update.asset.sha256.trim()) {
+		return Err(eyre::eyre!(
+			"checksum mismatch: expected {}, got {}",
+			update.asset.sha256,
+			digest
+		));
+	}
+
+	let update = update.clone();
+	let cores_dir = cores_dir.to_path_buf();
+	tokio::task::spawn_blocking(move || unpack(&bytes, &update, &cores_dir)).await?
+}
+
+fn unpack(bytes: &[u8], update: &CoreUpdate, cores_dir: &Path) -> eyre::Result<InstalledCore> {
+	let version_dir = cores_dir.join(update.version.to_string());
+	let staging_dir = cores_dir.join(format!(".{}.partial", update.version));
+	if staging_dir.exists() {
+		std::fs::remove_dir_all(&staging_dir)?;
+	}
+	std::fs::create_dir_all(&staging_dir)?;
+	let _staging = StagingGuard(staging_dir.clone());
+
+	let binary_name = Path::new(&update.asset.binary)
+		.file_name()
+		.ok_or_else(|| eyre::eyre!("invalid binary name {:?}", update.asset.binary))?;
+	let staged_binary = staging_dir.join(binary_name);
+
+	match update.asset.format {
+		ArchiveFormat::Raw => std::fs::write(&staged_binary, bytes)?,
+		ArchiveFormat::Gz => {
+			let mut data = Vec::new();
+			flate2::read::GzDecoder::new(bytes).read_to_end(&mut data)?;
+			std::fs::write(&staged_binary, data)?;
+		}
+		ArchiveFormat::TarGz => {
+			let mut archive = tar::Archive::new(flate2::read::GzDecoder::new(bytes));
+			let mut found = false;
+			for entry in archive.entries()? {
+				let mut entry = entry?;
+				let is_binary = entry.path()?.file_name() == Some(binary_name);
+				if is_binary && entry.header().entry_type().is_file() {
+					entry.unpack(&staged_binary)?;
+					found = true;
+					break;
+				}
+			}
+			if !found {
+				return Err(eyre::eyre!("{:?} not found in archive", update.asset.binary));
+			}
+		}
+	}
+
+	#[cfg(unix)]
+	{
+		use std::os::unix::fs::PermissionsExt;
+		std::fs::set_permissions(&staged_binary, std::fs::Permissions::from_mode(0o755))?;
+	}
+
+	// Reinstalling a version replaces it as a whole. The old directory is moved
+	// aside first so the version is never missing, and a core still running
+	// from it only loses the directory entry, not its binary.
+	let old_dir = cores_dir.join(format!(".{}.old", update.version));
+	if old_dir.exists() {
+		std::fs::remove_dir_all(&old_dir)?;
+	}
+	let replaced = version_dir.exists();
+	if replaced {
+		std::fs::rename(&version_dir, &old_dir)?;
+	}
+	if let Err(err) = std::fs::rename(&staging_dir, &version_dir) {
+		if replaced {
+			let _ = std::fs::rename(&old_dir, &version_dir);
+		}
+		return Err(err.into());
+	}
+	if replaced && let Err(err) = std::fs::remove_dir_all(&old_dir) {
+		tracing::warn!("Failed to remove replaced core {:?}: {}", old_dir, err);
+	}
+
+	let binary_path = version_dir.join(binary_name);
+	tracing::info!("Installed clash core {} at {:?}", update.version, binary_path);
+	Ok(InstalledCore {
+		version: update.version.clone(),
+		binary_path,
+	})
+}
+
+/// Removes the scratch directory of a failed install. After a successful one
+/// it has been renamed into place and there is nothing left to remove.
+struct StagingGuard(PathBuf);
+
+impl Drop for StagingGuard {
+	fn drop(&mut self) {
+		if self.0.exists()
+			&& let Err(err) = std::fs::remove_dir_all(&self.0)
+		{
+			tracing::warn!("Failed to remove core staging directory {:?}: {}", self.0, err);
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use tokio::{
+		io::{AsyncReadExt, AsyncWriteExt},
+		net::TcpListener,
+	};
+
+	use super::*;
+
+	/// Serves `routes` over plain HTTP on localhost and returns the base URL.
+	async fn serve(routes: Vec<(&'static str, Vec<u8>)>) -> String {
+		let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+		let addr = listener.local_addr().unwrap();
+		tokio::spawn(async move {
+			while let Ok((mut socket, _)) = listener.accept().await {
+				let mut request = Vec::new();
+				let mut buf = [0; 1024];
+				while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+					match socket.read(&mut buf).await {
+						Ok(0) | Err(_) => break,
+						Ok(n) => request.extend_from_slice(&buf[..n]),
+					}
+				}
+				let request = String::from_utf8_lossy(&request);
+				let path = request.split_whitespace().nth(1).unwrap_or_default();
+				let response = match routes.iter().find(|(route, _)| *route == path) {
+					Some((_, body)) => {
+						let mut response =
+							format!("HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n", body.len())
+								.into_bytes();
+						response.extend_from_slice(body);
+						response
+					}
+					None => b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_vec(),
+				};
+				let _ = socket.write_all(&response).await;
+			}
+		});
+		format!("http://{}", addr)
+	}
+
+	fn update(url: String, sha256: String) -> CoreUpdate {
+		CoreUpdate {
+			version: Version::new(0, 7, 1),
+			asset: ReleaseAsset {
+				target: host_target(),
+				url,
+				sha256,
+				format: ArchiveFormat::Raw,
+				binary: "clash".to_string(),
+			},
+		}
+	}
+
+	#[tokio::test]
+	async fn fetches_the_manifest_and_picks_the_latest_release() {
+		let manifest = br#"{ "releases": [
+			{ "version": "v0.7.0", "assets": [ { "target": "t", "url": "u0", "sha256": "00" } ] },
+			{ "version": "0.8.0", "assets": [ { "target": "other", "url": "x", "sha256": "00" } ] },
+			{ "version": "0.7.1", "assets": [
+				{ "target": "t", "url": "u1", "sha256": "11", "format": "tar.gz", "binary": "clash-rs" } ] }
+		] }"#;
+		let base = serve(vec![("/manifest.json", manifest.to_vec())]).await;
+		let client = reqwest::Client::new();
+
+		let manifest = fetch_manifest(&client, &format!("{}/manifest.json", base)).await.unwrap();
+		assert_eq!(manifest.releases.len(), 3);
+		assert_eq!(manifest.releases[0].assets[0].format, ArchiveFormat::Raw);
+		assert_eq!(manifest.releases[0].assets[0].binary, "clash");
+
+		let latest = latest_for(&manifest, "t").unwrap();
+		assert_eq!(latest.version, Version::new(0, 7, 1));
+		assert_eq!(latest.asset.url, "u1");
+		assert_eq!(latest.asset.format, ArchiveFormat::TarGz);
+		assert_eq!(latest_for(&manifest, "missing"), None);
+
+		assert!(fetch_manifest(&client, &format!("{}/gone.json", base)).await.is_err());
+	}
+
+	#[tokio::test]
+	async fn rejects_a_checksum_mismatch() {
+		let base = serve(vec![("/clash", b"#!/bin/sh\n".to_vec())]).await;
+		let cores = tempfile::tempdir().unwrap();
+		let update = update(format!("{}/clash", base), "00".repeat(32));
+
+		let err = install(&reqwest::Client::new(), &update, cores.path()).await.unwrap_err();
+		assert!(err.to_string().contains("checksum mismatch"));
+		assert!(!cores.path().join("0.7.1").exists());
+	}
+
+	#[tokio::test]
+	async fn installs_and_replaces_a_version() {
+		let old = b"old core".to_vec();
+		let new = b"new core".to_vec();
+		let base = serve(vec![("/old", old.clone()), ("/new", new.clone())]).await;
+		let cores = tempfile::tempdir().unwrap();
+		let client = reqwest::Client::new();
+
+		let first = update(format!("{}/old", base), format!("{:x}", Sha256::digest(&old)));
+		let installed = install(&client, &first, cores.path()).await.unwrap();
+		assert_eq!(installed.version, Version::new(0, 7, 1));
+		assert_eq!(installed.binary_path, cores.path().join("0.7.1").join("clash"));
+		assert_eq!(std::fs::read(&installed.binary_path).unwrap(), old);
+		#[cfg(unix)]
+		{
+			use std::os::unix::fs::PermissionsExt;
+			let mode = std::fs::metadata(&installed.binary_path).unwrap().permissions().mode();
+			assert_eq!(mode & 0o777, 0o755);
+		}
+
+		// A file held open from the old install stays readable after the swap
+		let mut running = std::fs::File::open(&installed.binary_path).unwrap();
+		let second = update(format!("{}/new", base), format!("{:X}", Sha256::digest(&new)));
+		let reinstalled = install(&client, &second, cores.path()).await.unwrap();
+		assert_eq!(std::fs::read(&reinstalled.binary_path).unwrap(), new);
+		let mut content = Vec::new();
+		running.read_to_end(&mut content).unwrap();
+		assert_eq!(content, old);
+
+		let mut leftovers: Vec<_> = std::fs::read_dir(cores.path())
+			.unwrap()
+			.map(|entry| entry.unwrap().file_name())
+			.collect();
+		leftovers.sort();
+		assert_eq!(leftovers, ["0.7.1"]);
+	}
+
+	#[test]
+	fn unpacks_a_tarball() {
+		let mut tarball = tar::Builder::new(flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::fast()));
+		for (name, data) in [("README", &b"docs"[..]), ("dist/clash-rs", &b"core"[..])] {
+			let mut header = tar::Header::new_gnu();
+			header.set_size(data.len() as u64);
+			header.set_mode(0o644);
+			header.set_cksum();
+			tarball.append_data(&mut header, name, data).unwrap();
+		}
+		let bytes = tarball.into_inner().unwrap().finish().unwrap();
+
+		let cores = tempfile::tempdir().unwrap();
+		let mut update = update(String::new(), String::new());
+		update.asset.format = ArchiveFormat::TarGz;
+		update.asset.binary = "clash-rs".to_string();
+		let installed = unpack(&bytes, &update, cores.path()).unwrap();
+		assert_eq!(std::fs::read(installed.binary_path).unwrap(), b"core");
+
+		update.asset.binary = "mihomo".to_string();
+		update.version = Version::new(0, 7, 2);
+		let err = unpack(&bytes, &update, cores.path()).unwrap_err();
+		assert!(err.to_string().contains("not found in archive"));
+
+		// The failed install leaves nothing behind
+		let mut leftovers: Vec<_> = std::fs::read_dir(cores.path())
+			.unwrap()
+			.map(|entry| entry.unwrap().file_name())
+			.collect();
+		leftovers.sort();
+		assert_eq!(leftovers, ["0.7.1"]);
+	}
+}
@@ -3,10 +3,19 @@
 pub mod api;
 pub mod app;
 pub mod config;
+pub mod core_info;
+pub mod core_manager;
 pub mod i18n;
 pub mod log;
+pub mod overrides;
 pub mod pages;
+pub mod preflight;
+pub mod profiles;
+pub mod readiness;
 pub mod sidecar;
+pub mod subscriptions;
+pub mod usage;
+pub mod widgets;
 
 // Re-exports
 pub use app::{AppModel, Message, SettingField};
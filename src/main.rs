@@ -3,12 +3,24 @@ use tracing::level_filters::LevelFilter;
 mod api;
 mod app;
 mod config;
+mod core_info;
+mod core_manager;
 mod i18n;
 mod log;
+mod overrides;
 mod pages;
+mod preflight;
+mod profiles;
+mod readiness;
 mod sidecar;
+mod subscriptions;
+mod usage;
+mod widgets;
 
 fn main() -> eyre::Result<()> {
+	// Before any other thread exists, see `usage::init_local_offset`
+	usage::init_local_offset();
+
 	// Get the system's preferred languages.
 	let requested_languages = i18n_embed::DesktopLanguageRequester::requested_languages();
 
@@ -0,0 +1,400 @@
+// SPDX-License-Identifier: AGPL3.0
+
+//! User override merged onto the selected profile before the core runs it,
+//! so subscription files stay untouched while our own settings always win.
+//!
+//! Maps in the override are merged key by key into the profile and any other
+//! value replaces the profile's. `prepend-<list>` and `append-<list>` add
+//! entries to the front or back of one of [`LIST_KEYS`] instead.
+
+use std::{
+	path::{Path, PathBuf},
+	sync::atomic::{AtomicU64, Ordering},
+};
+
+use eyre::{bail, eyre};
+use serde_yaml::{Mapping, Value};
+
+use crate::{
+	core_info::CoreFlavour,
+	preflight::{self, ConfigIssue},
+	profiles,
+};
+
+/// Lists the override can extend rather than replace.
+const LIST_KEYS: &[&str] = &["rules", "proxies"];
+
+const PREPEND: &str = "prepend-";
+const APPEND: &str = "append-";
+
+/// Written when the override is first opened for editing.
+const TEMPLATE: &str = "\
+# Applied on top of the selected profile every time the core starts or reloads.
+# Maps are merged key by key; any other value replaces the profile's.
+#
+# prepend-rules / append-rules and prepend-proxies / append-proxies add
+# entries to the profile's lists instead of replacing them:
+#
+# prepend-rules:
+#   - DOMAIN-SUFFIX,lan,DIRECT
+#
+# external-controller and secret always come from the app settings.
+";
+
+/// Controller settings the app needs in every config, applied last.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Controller {
+	pub address: String,
+	pub secret: Option<String>,
+}
+
+/// The override document, kept next to the app config.
+pub fn path() -> PathBuf {
+	dirs::config_dir()
+		.unwrap_or_else(|| PathBuf::from("."))
+		.join("cosmic-clash")
+		.join("override.yaml")
+}
+
+/// Absolute path of the generated config the core is launched with.
+pub fn runtime_path(config_dir: &Path) -> PathBuf {
+	let path = config_dir.join(format!("{}.yaml", profiles::RUNTIME_FILE));
+	std::path::absolute(&path).unwrap_or(path)
+}
+
+/// Where a new runtime config is written while the core checks it. Every
+/// call gets its own file, so two switches in flight cannot rename each
+/// other's half-checked config into place. The extension keeps it out of
+/// [`profiles::scan`].
+fn staging_path(config_dir: &Path) -> PathBuf {
+	static NEXT: AtomicU64 = AtomicU64::new(0);
+	let n = NEXT.fetch_add(1, Ordering::Relaxed);
+	runtime_path(config_dir).with_extension(format!("{}.{}.yaml.tmp", std::process::id(), n))
+}
+
+/// The last merged config the core rejected. Its extension keeps it out of
+/// [`profiles::scan`].
+pub fn rejected_path(config_dir: &Path) -> PathBuf {
+	runtime_path(config_dir).with_extension("yaml.rejected")
+}
+
+/// Creates the override from [`TEMPLATE`] unless it exists, returning its path.
+pub async fn ensure() -> eyre::Result<PathBuf> {
+	let path = path();
+	if !tokio::fs::try_exists(&path).await? {
+		if let Some(parent) = path.parent() {
+			tokio::fs::create_dir_all(parent).await?;
+		}
+		tokio::fs::write(&path, TEMPLATE).await?;
+	}
+	Ok(path)
+}
+
+/// Merges the override at `overlay` onto the profile at `source` and has
+/// `binary` check the result. Only a config that passes replaces the runtime
+/// one, so a restart after a rejected switch still runs what was running
+/// before.
+///
+/// A rejected config is kept at [`rejected_path`], which is the file the
+/// reported line refers to.
+pub async fn stage(
+	binary: &Path,
+	flavour: CoreFlavour,
+	work_dir: &Path,
+	source: &Path,
+	overlay: &Path,
+	controller: &Controller,
+) -> Result<(), ConfigIssue> {
+	if !source.is_file() {
+		return Err(issue(format!("{} does not exist", source.display())));
+	}
+	let merged = render(source, overlay, controller).await.map_err(|err| issue(err.to_string()))?;
+	let staged = staging_path(work_dir);
+	tokio::fs::write(&staged, merged)
+		.await
+		.map_err(|err| issue(format!("{}: {}", staged.display(), err)))?;
+	if let Err(mut issue) = preflight::validate_config(binary, flavour, work_dir, &staged).await {
+		let rejected = rejected_path(work_dir);
+		match tokio::fs::rename(&staged, &rejected).await {
+			Ok(()) => issue.file = Some(rejected),
+			Err(err) => {
+				tracing::warn!("Failed to keep the rejected config {:?}: {}", rejected, err);
+				let _ = tokio::fs::remove_file(&staged).await;
+			}
+		}
+		return Err(issue);
+	}
+	tokio::fs::rename(&staged, runtime_path(work_dir))
+		.await
+		.map_err(|err| issue(format!("{}: {}", staged.display(), err)))
+}
+
+fn issue(message: String) -> ConfigIssue {
+	ConfigIssue {
+		message,
+		line: None,
+		file: None,
+	}
+}
+
+/// Reads the profile at `source` and the override at `overlay` and returns
+/// the merged YAML. A missing override counts as empty.
+async fn render(source: &Path, overlay: &Path, controller: &Controller) -> eyre::Result<String> {
+	let profile = tokio::fs::read_to_string(source)
+		.await
+		.map_err(|err| eyre!("{}: {}", source.display(), err))?;
+	let overlay = match tokio::fs::read_to_string(overlay).await {
+		Ok(text) => text,
+		Err(err) if err.kind() == std::io::ErrorKind::NotFound => String::new(),
+		Err(err) => bail!("{}: {}", overlay.display(), err),
+	};
+	merge_documents(&profile, &overlay, controller)
+}
+
+/// Merges the YAML text of `overlay` onto `profile` and forces `controller`.
+pub fn merge_documents(profile: &str, overlay: &str, controller: &Controller) -> eyre::Result<String> {
+	let mut base: Value = serde_yaml::from_str(profile).map_err(|err| eyre!("profile: {}", err))?;
+	let overlay: Value = serde_yaml::from_str(overlay).map_err(|err| eyre!("override: {}", err))?;
+	// Resolve `<<` keys first so the override sees the values they stand for
+	base.apply_merge().map_err(|err| eyre!("profile: {}", err))?;
+
+	let Value::Mapping(base) = &mut base else {
+		bail!("profile is not a YAML mapping");
+	};
+	match overlay {
+		Value::Mapping(overlay) => apply(base, overlay)?,
+		// An empty or all-comment override
+		Value::Null => {}
+		_ => bail!("override is not a YAML mapping"),
+	}
+
+	base.insert("external-controller".into(), controller.address.clone().into());
+	// Without a secret set here the profile's would lock the app out
+	base.insert("secret".into(), controller.secret.clone().unwrap_or_default().into());
+
+	Ok(serde_yaml::to_string(base)?)
+}
+
+/// Applies an override mapping: plain keys first, then list directives, so
+/// the result does not depend on the order keys appear in.
+fn apply(base: &mut Mapping, overlay: Mapping) -> eyre::Result<()> {
+	let mut directives = Vec::new();
+	for (key, value) in overlay {
+		match key.as_str().and_then(directive) {
+			Some((prepend, list)) => directives.push((prepend, list, value)),
+			None => merge_value(base, key, value),
+		}
+	}
+
+	for (prepend, list, value) in directives {
+		let Value::Sequence(mut entries) = value else {
+			bail!("{}{} must be a list", if prepend { PREPEND } else { APPEND }, list);
+		};
+		let target = base.entry(list.into()).or_insert_with(|| Value::Sequence(Vec::new()));
+		let Value::Sequence(existing) = target else {
+			bail!("{} in the profile is not a list", list);
+		};
+		if prepend {
+			entries.append(existing);
+			*existing = entries;
+		} else {
+			existing.append(&mut entries);
+		}
+	}
+	Ok(())
+}
+
+/// Splits `prepend-rules` into `(true, "rules")`, if `key` is a directive.
+fn directive(key: &str) -> Option<(bool, &'static str)> {
+	let (prepend, list) = if let Some(list) = key.strip_prefix(PREPEND) {
+		(true, list)
+	} else {
+		(false, key.strip_prefix(APPEND)?)
+	};
+	LIST_KEYS.iter().find(|known| **known == list).map(|known| (prepend, *known))
+}
+
+/// Deep-merges one override entry: maps recurse, anything else replaces.
+fn merge_value(base: &mut Mapping, key: Value, value: Value) {
+	match (base.get_mut(&key), value) {
+		(Some(Value::Mapping(existing)), Value::Mapping(value)) => {
+			for (key, value) in value {
+				merge_value(existing, key, value);
+			}
+		}
+		(_, value) => {
+			base.insert(key, value);
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn controller() -> Controller {
+		Controller {
+			address: "127.0.0.1:9090".to_string(),
+			secret: Some("s3cret".to_string()),
+		}
+	}
+
+	fn merge(profile: &str, overlay: &str) -> Value {
+		serde_yaml::from_str(&merge_documents(profile, overlay, &controller()).unwrap()).unwrap()
+	}
+
+	fn yaml(text: &str) -> Value {
+		serde_yaml::from_str(text).unwrap()
+	}
+
+	#[test]
+	fn maps_merge_deeply_and_other_values_replace() {
+		let merged = merge(
+			"dns:\n  enable: false\n  nameserver: [1.1.1.1]\n  fallback-filter:\n    geoip: true\n\
+			 mode: rule\nrules: [MATCH,DIRECT]\n",
+			"dns:\n  enable: true\n  fallback-filter:\n    ipcidr: [240.0.0.0/4]\nmode: global\nrules: [MATCH,REJECT]\n",
+		);
+		assert_eq!(
+			merged["dns"],
+			yaml("enable: true\nnameserver: [1.1.1.1]\nfallback-filter:\n  geoip: true\n  ipcidr: [240.0.0.0/4]\n")
+		);
+		assert_eq!(merged["mode"], yaml("global"));
+		assert_eq!(merged["rules"], yaml("[MATCH,REJECT]"));
+	}
+
+	#[test]
+	fn directives_prepend_and_append_in_order() {
+		let merged = merge(
+			"rules: [b, c]\nproxies: [{ name: p1 }]\n",
+			"append-proxies: [{ name: p2 }, { name: p3 }]\nprepend-rules: [a1, a2]\nappend-rules: [d]\n",
+		);
+		assert_eq!(merged["rules"], yaml("[a1, a2, b, c, d]"));
+		assert_eq!(merged["proxies"], yaml("[{ name: p1 }, { name: p2 }, { name: p3 }]"));
+		assert!(merged.get("prepend-rules").is_none());
+	}
+
+	#[test]
+	fn directives_run_after_plain_keys() {
+		let merged = merge("rules: [old]\n", "prepend-rules: [first]\nrules: [new]\n");
+		assert_eq!(merged["rules"], yaml("[first, new]"));
+	}
+
+	#[test]
+	fn directives_create_a_missing_list() {
+		let merged = merge("mode: rule\n", "append-proxies: [{ name: p1 }]\n");
+		assert_eq!(merged["proxies"], yaml("[{ name: p1 }]"));
+	}
+
+	#[test]
+	fn directives_need_lists() {
+		let err = merge_documents("rules: []\n", "prepend-rules: MATCH\n", &controller()).unwrap_err();
+		assert_eq!(err.to_string(), "prepend-rules must be a list");
+		let err = merge_documents("rules: nope\n", "append-rules: [MATCH]\n", &controller()).unwrap_err();
+		assert_eq!(err.to_string(), "rules in the profile is not a list");
+	}
+
+	#[test]
+	fn directive_only_knows_list_keys() {
+		assert_eq!(directive("prepend-rules"), Some((true, "rules")));
+		assert_eq!(directive("append-proxies"), Some((false, "proxies")));
+		assert_eq!(directive("append-dns"), None);
+		assert_eq!(directive("rules"), None);
+		// Unknown directives are plain keys and land in the config as they are
+		let merged = merge("mode: rule\n", "append-dns: [x]\n");
+		assert_eq!(merged["append-dns"], yaml("[x]"));
+	}
+
+	#[test]
+	fn empty_and_comment_only_overrides_change_nothing_else() {
+		for overlay in ["", "# nothing yet\n", TEMPLATE] {
+			let merged = merge("mode: rule\nrules: [MATCH,DIRECT]\n", overlay);
+			assert_eq!(merged["mode"], yaml("rule"));
+			assert_eq!(merged["rules"], yaml("[MATCH,DIRECT]"));
+		}
+	}
+
+	#[test]
+	fn override_must_be_a_mapping() {
+		let err = merge_documents("mode: rule\n", "- a\n", &controller()).unwrap_err();
+		assert_eq!(err.to_string(), "override is not a YAML mapping");
+		let err = merge_documents("- a\n", "", &controller()).unwrap_err();
+		assert_eq!(err.to_string(), "profile is not a YAML mapping");
+	}
+
+	#[test]
+	fn merge_keys_resolve_before_the_override() {
+		let merged = merge(
+			"base: &base\n  type: ss\n  port: 1\nproxy:\n  <<: *base\n  name: p1\n",
+			"proxy:\n  port: 2\n",
+		);
+		assert_eq!(merged["proxy"], yaml("type: ss\nport: 2\nname: p1\n"));
+	}
+
+	#[test]
+	fn controller_settings_always_win() {
+		let merged = merge(
+			"external-controller: 0.0.0.0:9999\nsecret: theirs\n",
+			"external-controller: 0.0.0.0:8888\nsecret: mine\n",
+		);
+		assert_eq!(merged["external-controller"], yaml("127.0.0.1:9090"));
+		assert_eq!(merged["secret"], yaml("s3cret"));
+
+		let open = Controller {
+			secret: None,
+			..controller()
+		};
+		let merged: Value = serde_yaml::from_str(&merge_documents("secret: theirs\n", "", &open).unwrap()).unwrap();
+		assert_eq!(merged["secret"], yaml("''"));
+	}
+
+	#[test]
+	fn staging_paths_are_unique() {
+		let dir = Path::new("/tmp/clash");
+		let (first, second) = (staging_path(dir), staging_path(dir));
+		assert_ne!(first, second);
+		for path in [first, second] {
+			assert_eq!(path.parent(), Some(dir));
+			assert!(path.to_string_lossy().ends_with(".yaml.tmp"));
+		}
+	}
+
+	#[cfg(unix)]
+	#[tokio::test]
+	async fn only_a_passing_config_replaces_the_runtime_one() {
+		let dir = tempfile::tempdir().unwrap();
+		let source = dir.path().join("work.yaml");
+		std::fs::write(&source, "mode: rule\n").unwrap();
+		let overlay = dir.path().join("override.yaml");
+		std::fs::write(&overlay, "append-rules: [MATCH,DIRECT]\n").unwrap();
+		let runtime = runtime_path(dir.path());
+		std::fs::write(&runtime, "previous\n").unwrap();
+		let leftovers = || {
+			std::fs::read_dir(dir.path())
+				.unwrap()
+				.filter(|entry| entry.as_ref().unwrap().path().to_string_lossy().ends_with(".tmp"))
+				.count()
+		};
+
+		// `false` rejects every config, `true` accepts it
+		let issue = stage(Path::new("false"), CoreFlavour::ClashRs, dir.path(), &source, &overlay, &controller())
+			.await
+			.unwrap_err();
+		assert_eq!(std::fs::read_to_string(&runtime).unwrap(), "previous\n");
+		assert_eq!(leftovers(), 0);
+		// The rejected merge stays behind for the reported line to point into
+		let rejected = rejected_path(dir.path());
+		assert_eq!(issue.file.as_ref(), Some(&rejected));
+		assert!(std::fs::read_to_string(&rejected).unwrap().contains("mode: rule"));
+
+		stage(Path::new("true"), CoreFlavour::ClashRs, dir.path(), &source, &overlay, &controller()).await.unwrap();
+		let staged: Value = serde_yaml::from_str(&std::fs::read_to_string(&runtime).unwrap()).unwrap();
+		assert_eq!(staged, yaml("mode: rule\nrules: [MATCH,DIRECT]\nexternal-controller: 127.0.0.1:9090\nsecret: s3cret\n"));
+
+		// Without an override the profile goes through as it is
+		std::fs::remove_file(&overlay).unwrap();
+		stage(Path::new("true"), CoreFlavour::ClashRs, dir.path(), &source, &overlay, &controller()).await.unwrap();
+		let staged: Value = serde_yaml::from_str(&std::fs::read_to_string(&runtime).unwrap()).unwrap();
+		assert_eq!(staged, yaml("mode: rule\nexternal-controller: 127.0.0.1:9090\nsecret: s3cret\n"));
+		assert_eq!(leftovers(), 0);
+	}
+}
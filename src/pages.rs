@@ -1,7 +1,19 @@
+pub mod connections;
 pub mod home;
+pub mod logs;
 pub mod profile;
+pub mod providers;
+pub mod proxies;
+pub mod rules;
 pub mod settings;
+pub mod usage;
 
+pub use connections::view_connections;
 pub use home::view_home;
+pub use logs::view_logs;
 pub use profile::view_profile;
+pub use providers::view_providers;
+pub use proxies::view_proxies;
+pub use rules::view_rules;
 pub use settings::view_settings;
+pub use usage::view_usage;
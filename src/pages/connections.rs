@@ -0,0 +1,336 @@
+// SPDX-License-Identifier: AGPL3.0
+
+use std::cmp::Ordering;
+
+use cosmic::{
+	Element,
+	cosmic_theme,
+	iced::{Alignment, Background, Border, Length},
+	theme, widget,
+};
+
+use crate::{
+	api::Connection,
+	app::{AppModel, ConnectionSort, Message},
+	fl,
+	pages::home::api_error_row,
+};
+
+/// Table columns, left to right, with their relative widths.
+const COLUMNS: [(ConnectionSort, u16); 7] = [
+	(ConnectionSort::Destination, 4),
+	(ConnectionSort::Process, 2),
+	(ConnectionSort::Rule, 3),
+	(ConnectionSort::Chain, 3),
+	(ConnectionSort::Upload, 2),
+	(ConnectionSort::Download, 2),
+	(ConnectionSort::Start, 2),
+];
+
+pub fn view_connections(app: &AppModel, _space_s: u16) -> Element<'_, Message> {
+	let cosmic_theme::Spacing {
+		space_xxs,
+		space_xs,
+		space_s,
+		space_m,
+		space_l,
+		..
+	} = theme::active().cosmic().spacing;
+
+	let total = app.session.connections.as_ref().map_or(0, |s| s.connections.len());
+	let header = widget::row::with_capacity(3)
+		.push(widget::text::title2(fl!("connections")))
+		.push(widget::space::horizontal().width(Length::Fill))
+		.push(
+			widget::button::destructive(fl!("close-all"))
+				.on_press_maybe((app.session.api.is_some() && total > 0).then_some(Message::CloseAllConnections)),
+		)
+		.align_y(Alignment::Center)
+		.spacing(space_s)
+		.width(Length::Fill);
+
+	let mut layout = widget::column::with_capacity(4)
+		.push(header)
+		.spacing(space_m)
+		.width(Length::Fill);
+
+	if let Some(err) = app.controller_error() {
+		layout = layout.push(
+			widget::container(api_error_row(err, space_xs))
+				.padding(space_l)
+				.width(Length::Fill)
+				.class(theme::Container::Card),
+		);
+	}
+
+	let Some(snapshot) = app.session.connections.as_ref().filter(|_| app.session.api.is_some()) else {
+		return layout.push(empty_connections(app, space_s, space_m, space_l)).into();
+	};
+
+	let connections = visible_connections(app, &snapshot.connections);
+
+	let summary = widget::row::with_capacity(3)
+		.push(widget::text::caption(fl!(
+			"connections-shown",
+			shown = connections.len().to_string(),
+			total = total.to_string()
+		)))
+		.push(widget::space::horizontal().width(Length::Fill))
+		.push(widget::text::caption(fl!(
+			"connections-totals",
+			up = format_bytes(snapshot.upload_total),
+			down = format_bytes(snapshot.download_total)
+		)))
+		.align_y(Alignment::Center)
+		.width(Length::Fill);
+
+	let filter = widget::text_input(fl!("filter-connections"), &app.connection_filter)
+		.on_input(Message::ConnectionFilterChanged)
+		.padding([space_xs, space_s])
+		.width(Length::Fill);
+
+	let mut table = widget::column::with_capacity(connections.len() + 2)
+		.push(table_header(app, space_xs))
+		.push(widget::divider::horizontal::default())
+		.spacing(space_xxs)
+		.width(Length::Fill);
+
+	for conn in connections {
+		table = table.push(connection_row(conn, space_xs));
+	}
+
+	layout
+		.push(
+			widget::container(
+				widget::column::with_capacity(3)
+					.push(filter)
+					.push(summary)
+					.push(table)
+					.spacing(space_s)
+					.width(Length::Fill),
+			)
+			.padding(space_l)
+			.width(Length::Fill)
+			.class(theme::Container::Card),
+		)
+		.into()
+}
+
+fn empty_connections(app: &AppModel, space_s: u16, space_m: u16, space_l: u16) -> Element<'static, Message> {
+	let icon = widget::container(widget::icon::from_name("network-transmit-receive-symbolic").size(48))
+		.padding(space_m)
+		.class(theme::Container::custom(|t| {
+			let cosmic = t.cosmic();
+			widget::container::Style {
+				background: Some(Background::Color(cosmic.bg_component_color().into())),
+				icon_color: Some(cosmic.on_bg_component_color().into()),
+				border: Border { radius: 64.0.into(), ..Default::default() },
+				..Default::default()
+			}
+		}));
+
+	let description = if app.session.api.is_none() {
+		fl!("connections-core-stopped")
+	} else {
+		fl!("connections-loading")
+	};
+
+	let column = widget::column::with_capacity(2)
+		.align_x(Alignment::Center)
+		.spacing(space_s)
+		.width(Length::Fill)
+		.push(icon)
+		.push(widget::text::body(description));
+
+	widget::container(column)
+		.padding(space_l + space_m)
+		.width(Length::Fill)
+		.class(theme::Container::Card)
+		.into()
+}
+
+/// Connections matching the filter, in the chosen order.
+fn visible_connections<'a>(app: &AppModel, connections: &'a [Connection]) -> Vec<&'a Connection> {
+	let needle = app.connection_filter.trim().to_lowercase();
+	let mut visible: Vec<&Connection> = connections
+		.iter()
+		.filter(|conn| needle.is_empty() || matches_filter(conn, &needle))
+		.collect();
+
+	visible.sort_by(|a, b| {
+		let ordering = compare(a, b, app.connection_sort);
+		if app.connection_sort_descending {
+			ordering.reverse()
+		} else {
+			ordering
+		}
+	});
+	visible
+}
+
+fn matches_filter(conn: &Connection, needle: &str) -> bool {
+	let meta = &conn.metadata;
+	let contains = |field: &str| field.to_lowercase().contains(needle);
+	contains(&conn.destination())
+		|| contains(&meta.source_ip)
+		|| contains(&meta.network)
+		|| contains(&meta.process_path)
+		|| contains(&conn.rule)
+		|| contains(&conn.rule_payload)
+		|| conn.chains.iter().any(|link| contains(link))
+}
+
+fn compare(a: &Connection, b: &Connection, column: ConnectionSort) -> Ordering {
+	match column {
+		ConnectionSort::Destination => a.destination().cmp(&b.destination()),
+		ConnectionSort::Process => a.process().cmp(&b.process()),
+		ConnectionSort::Rule => a.rule.cmp(&b.rule).then_with(|| a.rule_payload.cmp(&b.rule_payload)),
+		ConnectionSort::Chain => a.chains.first().cmp(&b.chains.first()),
+		ConnectionSort::Upload => a.upload.cmp(&b.upload),
+		ConnectionSort::Download => a.download.cmp(&b.download),
+		// RFC 3339 timestamps from one core sort correctly as text
+		ConnectionSort::Start => a.start.cmp(&b.start),
+	}
+}
+
+fn table_header(app: &AppModel, space_xs: u16) -> Element<'static, Message> {
+	let mut row = widget::row::with_capacity(COLUMNS.len() + 1)
+		.spacing(space_xs)
+		.align_y(Alignment::Center)
+		.width(Length::Fill);
+
+	for (column, portion) in COLUMNS {
+		let label = match column {
+			ConnectionSort::Destination => fl!("destination"),
+			ConnectionSort::Process => fl!("process"),
+			ConnectionSort::Rule => fl!("rule"),
+			ConnectionSort::Chain => fl!("chain"),
+			ConnectionSort::Upload => fl!("upload"),
+			ConnectionSort::Download => fl!("download"),
+			ConnectionSort::Start => fl!("started"),
+		};
+		let label = match (app.connection_sort == column, app.connection_sort_descending) {
+			(true, true) => format!("{} ↓", label),
+			(true, false) => format!("{} ↑", label),
+			(false, _) => label,
+		};
+		row = row.push(
+			widget::button::text(label)
+				.on_press(Message::SortConnections(column))
+				.width(Length::FillPortion(portion)),
+		);
+	}
+
+	// Room for the close buttons
+	row.push(widget::space::horizontal().width(Length::Fixed(32.0))).into()
+}
+
+fn connection_row(conn: &Connection, space_xs: u16) -> Element<'static, Message> {
+	// Clash lists the chain from the proxy outwards; show it group first
+	let chain = conn.chains.iter().rev().cloned().collect::<Vec<_>>().join(" → ");
+	let rule = if conn.rule_payload.is_empty() {
+		conn.rule.clone()
+	} else {
+		format!("{} ({})", conn.rule, conn.rule_payload)
+	};
+	let cells = [
+		conn.destination(),
+		conn.process().unwrap_or_default().to_string(),
+		rule,
+		chain,
+		format_bytes(conn.upload),
+		format_bytes(conn.download),
+		start_time(&conn.start),
+	];
+
+	let mut row = widget::row::with_capacity(cells.len() + 1)
+		.spacing(space_xs)
+		.align_y(Alignment::Center)
+		.width(Length::Fill);
+
+	for ((_, portion), text) in COLUMNS.iter().zip(cells) {
+		row = row.push(widget::text::body(text).width(Length::FillPortion(*portion)));
+	}
+
+	row.push(
+		widget::button::icon(widget::icon::from_name("window-close-symbolic"))
+			.on_press(Message::CloseConnection(conn.id.clone())),
+	)
+	.into()
+}
+
+/// Clock time out of an RFC 3339 timestamp, e.g. `12:03:44`.
+fn start_time(start: &str) -> String {
+	start
+		.split_once('T')
+		.map(|(_, time)| time.chars().take(8).collect())
+		.unwrap_or_else(|| start.to_string())
+}
+
+pub fn format_bytes(bytes: u64) -> String {
+	let kb = bytes as f64 / 1024.0;
+	if kb < 1.0 {
+		format!("{} B", bytes)
+	} else if kb < 1024.0 {
+		format!("{:.1} KB", kb)
+	} else if kb < 1024.0 * 1024.0 {
+		format!("{:.1} MB", kb / 1024.0)
+	} else {
+		format!("{:.2} GB", kb / 1024.0 / 1024.0)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn connection(host: &str, process_path: &str, upload: u64, start: &str, chains: &[&str]) -> Connection {
+		serde_json::from_value(serde_json::json!({
+			"id": host,
+			"metadata": {
+				"network": "tcp",
+				"sourceIP": "192.168.1.20",
+				"destinationIP": "93.184.216.34",
+				"destinationPort": 443,
+				"host": host,
+				"processPath": process_path,
+			},
+			"upload": upload,
+			"download": upload * 10,
+			"start": start,
+			"chains": chains,
+			"rule": "DomainSuffix",
+			"rulePayload": "example.com",
+		}))
+		.unwrap()
+	}
+
+	#[test]
+	fn filter_matches_host_process_and_chain() {
+		let conn = connection("www.Example.com", "/usr/bin/Firefox", 0, "", &["hk-1", "Proxy"]);
+		for needle in ["example.com", "firefox", "/usr/bin", "hk-1", "proxy", "192.168.1"] {
+			assert!(matches_filter(&conn, needle), "{needle} should match");
+		}
+		assert!(!matches_filter(&conn, "chrome"));
+
+		// Without a sniffed host the destination address is searched
+		let conn = connection("", "", 0, "", &[]);
+		assert!(matches_filter(&conn, "93.184.216.34:443"));
+	}
+
+	#[test]
+	fn sort_keys_compare_by_column() {
+		let a = connection("a.example", "", 900, "2024-05-01T12:00:00Z", &["DIRECT"]);
+		let b = connection("b.example", "/usr/bin/curl", 10_000, "2024-05-01T09:30:00Z", &["hk-1"]);
+
+		assert_eq!(compare(&a, &b, ConnectionSort::Destination), Ordering::Less);
+		// Connections without a process sort first
+		assert_eq!(compare(&a, &b, ConnectionSort::Process), Ordering::Less);
+		assert_eq!(compare(&a, &b, ConnectionSort::Chain), Ordering::Less);
+		// Byte counts compare as numbers, not text
+		assert_eq!(compare(&a, &b, ConnectionSort::Upload), Ordering::Less);
+		assert_eq!(compare(&a, &b, ConnectionSort::Download), Ordering::Less);
+		assert_eq!(compare(&a, &b, ConnectionSort::Start), Ordering::Greater);
+		assert_eq!(compare(&a, &b, ConnectionSort::Rule), Ordering::Equal);
+	}
+}
@@ -8,10 +8,19 @@ use cosmic::{
 };
 
 use crate::{
-	app::{AppModel, Message},
+	api::{ClashApiError, RunMode},
+	app::{AppModel, ExistingCore, Message},
 	fl,
+	pages::connections::format_bytes,
+	widgets::{traffic_chart, traffic_chart::ChartWindow},
+	preflight::ConfigIssue,
+	readiness::{Readiness, ReadinessError},
+	sidecar::{OutputLine, SidecarStatus},
 };
 
+/// Number of core output lines shown when the core is down.
+const CORE_OUTPUT_LINES: usize = 12;
+
 pub fn view_home(app: &AppModel, _space_s: u16) -> Element<'_, Message> {
 	let cosmic_theme::Spacing {
 		space_xxxs,
@@ -24,7 +33,7 @@ pub fn view_home(app: &AppModel, _space_s: u16) -> Element<'_, Message> {
 	} = theme::active().cosmic().spacing;
 
 	if !app.vpn_is_active {
-		return empty_state(space_xxs, space_xs, space_s, space_m, space_l);
+		return empty_state(app, space_xxs, space_xs, space_s, space_m, space_l);
 	}
 
 	let active_card = active_status_card(app, space_xxxs, space_xxs, space_xs, space_s, space_m, space_l);
@@ -34,14 +43,21 @@ pub fn view_home(app: &AppModel, _space_s: u16) -> Element<'_, Message> {
 		.spacing(space_m)
 		.width(Length::Fill);
 
-	if let Some(traffic) = &app.traffic {
-		layout = layout.push(stats_card(traffic, space_xxs, space_xs, space_s, space_m, space_l));
+	if let Some(traffic) = &app.session.traffic {
+		layout = layout.push(stats_card(app, traffic, space_xxs, space_xs, space_s, space_m, space_l));
 	}
 
 	layout.into()
 }
 
-fn empty_state(_space_xxs: u16, _space_xs: u16, space_s: u16, space_m: u16, _space_l: u16) -> Element<'static, Message> {
+fn empty_state(
+	app: &AppModel,
+	space_xxs: u16,
+	_space_xs: u16,
+	space_s: u16,
+	space_m: u16,
+	_space_l: u16,
+) -> Element<'static, Message> {
 	let icon = widget::container(
 		widget::icon::from_name("network-wireless-disabled-symbolic")
 			.size(64),
@@ -58,9 +74,30 @@ fn empty_state(_space_xxs: u16, _space_xs: u16, space_s: u16, space_m: u16, _spa
 		}
 	}));
 
-	let start_button = widget::button::suggested(fl!("start-vpn")).on_press(Message::ToggleVPN);
+	let (title, description, start_button) = match &app.readiness {
+		Readiness::Starting => (
+			fl!("vpn-starting"),
+			fl!("vpn-starting-description"),
+			widget::button::standard(fl!("cancel")).on_press(Message::ToggleVPN),
+		),
+		Readiness::Stopping => (
+			fl!("vpn-stopping"),
+			fl!("vpn-stopping-description"),
+			widget::button::suggested(fl!("start-vpn")),
+		),
+		Readiness::Failed(err) => (
+			fl!("vpn-failed"),
+			readiness_error_text(err),
+			widget::button::suggested(fl!("start-vpn")).on_press(Message::ToggleVPN),
+		),
+		_ => (
+			fl!("no-vpn-running"),
+			fl!("no-vpn-running-description"),
+			widget::button::suggested(fl!("start-vpn")).on_press(Message::ToggleVPN),
+		),
+	};
 
-	let column = widget::column::with_capacity(5)
+	let mut column = widget::column::with_capacity(6)
 		.align_x(Alignment::Center)
 		.spacing(space_m)
 		.width(Length::Fill)
@@ -71,11 +108,24 @@ fn empty_state(_space_xxs: u16, _space_xs: u16, space_s: u16, space_m: u16, _spa
 			widget::column::with_capacity(2)
 				.align_x(Alignment::Center)
 				.spacing(space_s)
-				.push(widget::text::title2(fl!("no-vpn-running")))
-				.push(widget::text::body(fl!("no-vpn-running-description"))),
-		)
-		.push(start_button)
-		.push(widget::space::vertical().height(Length::Fill));
+				.push(widget::text::title2(title))
+				.push(widget::text::body(description)),
+		);
+
+	// A leftover core must be dealt with before we can spawn our own
+	column = match &app.existing_core {
+		Some(core) => column.push(existing_core_card(app, core, space_xxs, space_s, space_m)),
+		None => column.push(start_button),
+	};
+
+	if matches!(app.readiness, Readiness::Failed(_)) {
+		let lines = app.core_output(CORE_OUTPUT_LINES);
+		if !lines.is_empty() {
+			column = column.push(core_output(lines, space_xxs, space_s));
+		}
+	}
+
+	let column = column.push(widget::space::vertical().height(Length::Fill));
 
 	widget::container(column)
 		.width(Length::Fill)
@@ -83,6 +133,107 @@ fn empty_state(_space_xxs: u16, _space_xs: u16, space_s: u16, space_m: u16, _spa
 		.into()
 }
 
+fn existing_core_card(
+	app: &AppModel,
+	core: &ExistingCore,
+	space_xxs: u16,
+	space_s: u16,
+	space_m: u16,
+) -> Element<'static, Message> {
+	let description = match (&core.version, core.pid) {
+		_ if core.unauthorized => fl!("existing-core-unauthorized", port = app.config.api_port.to_string()),
+		(Some(version), Some(pid)) => fl!(
+			"existing-core-pid-version",
+			pid = pid.to_string(),
+			version = version.clone(),
+			port = app.config.api_port.to_string()
+		),
+		(Some(version), None) => fl!(
+			"existing-core-version",
+			version = version.clone(),
+			port = app.config.api_port.to_string()
+		),
+		(None, pid) => fl!("existing-core-pid", pid = pid.unwrap_or_default().to_string()),
+	};
+
+	let mut actions =
+		widget::row::with_capacity(4).push(widget::button::standard(fl!("change-port")).on_press(Message::ChangeApiPort));
+	if core.unauthorized {
+		actions = actions.push(widget::button::standard(fl!("change-secret")).on_press(Message::ChangeApiSecret));
+	}
+	let actions = actions
+		.push(
+			widget::button::destructive(fl!("kill-core"))
+				.on_press_maybe(core.pid.map(|_| Message::KillExistingCore)),
+		)
+		.push(
+			// A core found only by its port could not be stopped once adopted
+			widget::button::suggested(fl!("adopt-core"))
+				.on_press_maybe((core.version.is_some() && core.pid.is_some()).then_some(Message::AdoptExistingCore)),
+		)
+		.spacing(space_s);
+
+	widget::container(
+		widget::column::with_capacity(3)
+			.push(widget::text::heading(fl!("existing-core-found")))
+			.push(widget::text::body(description))
+			.push(actions)
+			.spacing(space_xxs)
+			.align_x(Alignment::Center),
+	)
+	.padding(space_m)
+	.class(theme::Container::Card)
+	.into()
+}
+
+fn readiness_error_text(err: &ReadinessError) -> String {
+	match err {
+		ReadinessError::InvalidConfig(issue) => config_issue_text(issue),
+		ReadinessError::Spawn(reason) => fl!("core-spawn-failed", reason = reason.clone()),
+		ReadinessError::Exited { exit_code: Some(code) } => fl!("core-exited-code", code = code.to_string()),
+		ReadinessError::Exited { exit_code: None } => fl!("core-exited"),
+		ReadinessError::Timeout(timeout) => fl!("core-ready-timeout", secs = timeout.as_secs().to_string()),
+		ReadinessError::Api(err) => api_error_text(err),
+	}
+}
+
+pub fn api_error_text(err: &ClashApiError) -> String {
+	match err {
+		ClashApiError::ConnectionRefused => fl!("api-unreachable"),
+		ClashApiError::Timeout => fl!("api-timeout"),
+		ClashApiError::Unauthorized => fl!("api-unauthorized"),
+		ClashApiError::Status { status, message } => {
+			fl!("api-rejected", status = status.to_string(), message = message.clone())
+		}
+		ClashApiError::Decode(details) => fl!("api-decode", details = details.clone()),
+		ClashApiError::Request(details) => fl!("api-request", details = details.clone()),
+	}
+}
+
+/// Inline warning row used for controller errors.
+pub fn api_error_row(err: &ClashApiError, space_xs: u16) -> Element<'static, Message> {
+	widget::row::with_capacity(2)
+		.push(widget::icon::from_name("dialog-warning-symbolic").size(16))
+		.push(widget::text::body(api_error_text(err)))
+		.spacing(space_xs)
+		.align_y(Alignment::Center)
+		.width(Length::Fill)
+		.into()
+}
+
+pub fn config_issue_text(issue: &ConfigIssue) -> String {
+	match (issue.line, &issue.file) {
+		(Some(line), Some(file)) => fl!(
+			"config-invalid-file-line",
+			file = file.display().to_string(),
+			line = line.to_string(),
+			message = issue.message.clone()
+		),
+		(Some(line), None) => fl!("config-invalid-line", line = line.to_string(), message = issue.message.clone()),
+		(None, _) => fl!("config-invalid", message = issue.message.clone()),
+	}
+}
+
 fn active_status_card(
 	app: &AppModel,
 	_space_xxxs: u16,
@@ -92,16 +243,22 @@ fn active_status_card(
 	_space_m: u16,
 	space_l: u16,
 ) -> Element<'_, Message> {
+	let (status_label, status_color): (String, fn(&cosmic::Theme) -> cosmic::iced::Color) = match &app.sidecar_status {
+		SidecarStatus::Restarting { attempt, .. } => (
+			fl!("vpn-restarting", attempt = attempt.to_string()),
+			|t| t.cosmic().warning_color().into(),
+		),
+		SidecarStatus::Crashed { .. } => (fl!("vpn-crashed"), |t| t.cosmic().destructive_color().into()),
+		_ => (fl!("vpn-running"), |t| t.cosmic().success_color().into()),
+	};
+
 	let dot = widget::container(widget::text(""))
 		.width(Length::Fixed(10.0))
 		.height(Length::Fixed(10.0))
-		.class(theme::Container::custom(|t| {
-			let cosmic = t.cosmic();
-			widget::container::Style {
-				background: Some(Background::Color(cosmic.success_color().into())),
-				border: Border { radius: 5.0.into(), ..Default::default() },
-				..Default::default()
-			}
+		.class(theme::Container::custom(move |t| widget::container::Style {
+			background: Some(Background::Color(status_color(t))),
+			border: Border { radius: 5.0.into(), ..Default::default() },
+			..Default::default()
 		}));
 
 	let header = widget::row::with_capacity(4)
@@ -111,7 +268,7 @@ fn active_status_card(
 		.push(
 			widget::row::with_capacity(2)
 				.push(dot)
-				.push(widget::text::body(fl!("vpn-running")))
+				.push(widget::text::body(status_label))
 				.spacing(space_xs)
 				.align_y(Alignment::Center),
 		)
@@ -121,10 +278,26 @@ fn active_status_card(
 
 	let mut details = widget::column::with_capacity(2).spacing(space_xs).width(Length::Fill);
 
-	if let Some(version) = &app.clash_version {
+	if let Some(version) = &app.session.clash_version {
 		details = details.push(meta_row(fl!("clash-version"), version.clone(), space_xxs));
 	}
 
+	if let Some(config) = &app.session.runtime_config {
+		details = details.push(mode_row(config.mode, space_xs));
+	}
+
+	if let Some(err) = app.controller_error() {
+		details = details.push(api_error_row(err, space_xs));
+	}
+
+	// Surface what the core printed before it went down
+	if matches!(app.sidecar_status, SidecarStatus::Restarting { .. } | SidecarStatus::Crashed { .. }) {
+		let lines = app.core_output(CORE_OUTPUT_LINES);
+		if !lines.is_empty() {
+			details = details.push(core_output(lines, space_xxs, space_s));
+		}
+	}
+
 	let footer = widget::row::with_capacity(2)
 		.push(widget::space::horizontal().width(Length::Fill))
 		.push(widget::button::destructive(fl!("stop-vpn")).on_press(Message::ToggleVPN))
@@ -145,6 +318,56 @@ fn active_status_card(
 	.into()
 }
 
+fn core_output(lines: Vec<OutputLine>, space_xxs: u16, space_s: u16) -> Element<'static, Message> {
+	let mut column = widget::column::with_capacity(lines.len() + 1)
+		.push(widget::text::caption(fl!("core-output")))
+		.spacing(space_xxs)
+		.width(Length::Fill);
+
+	for line in lines {
+		column = column.push(widget::text::monotext(line.text));
+	}
+
+	widget::container(column)
+		.padding(space_s)
+		.width(Length::Fill)
+		.class(theme::Container::custom(|t| {
+			let cosmic = t.cosmic();
+			widget::container::Style {
+				background: Some(Background::Color(cosmic.bg_component_color().into())),
+				text_color: Some(cosmic.on_bg_component_color().into()),
+				border: Border { radius: 8.0.into(), ..Default::default() },
+				..Default::default()
+			}
+		}))
+		.into()
+}
+
+/// Rule/global/direct switch; the active mode is highlighted.
+fn mode_row(current: Option<RunMode>, space_xs: u16) -> Element<'static, Message> {
+	let mut buttons = widget::row::with_capacity(RunMode::ALL.len()).spacing(space_xs);
+	for mode in RunMode::ALL {
+		let label = match mode {
+			RunMode::Rule => fl!("mode-rule"),
+			RunMode::Global => fl!("mode-global"),
+			RunMode::Direct => fl!("mode-direct"),
+		};
+		buttons = buttons.push(if current == Some(mode) {
+			widget::button::suggested(label)
+		} else {
+			widget::button::standard(label).on_press(Message::SetRunMode(mode))
+		});
+	}
+
+	widget::row::with_capacity(3)
+		.push(widget::text::caption(fl!("run-mode")))
+		.push(widget::space::horizontal().width(Length::Fill))
+		.push(buttons)
+		.align_y(Alignment::Center)
+		.width(Length::Fill)
+		.into()
+}
+
 fn meta_row(label: String, value: String, _spacing: u16) -> Element<'static, Message> {
 	widget::row::with_capacity(3)
 		.push(widget::text::caption(label))
@@ -155,28 +378,62 @@ fn meta_row(label: String, value: String, _spacing: u16) -> Element<'static, Mes
 		.into()
 }
 
-fn stats_card(
+fn format_rate(bytes: u64) -> String {
+	let kb = bytes as f64 / 1024.0;
+	if kb < 1024.0 {
+		format!("{:.1} KB/s", kb)
+	} else {
+		format!("{:.2} MB/s", kb / 1024.0)
+	}
+}
+
+fn peak_label(bytes: u64) -> String {
+	fl!("traffic-peak", rate = format_rate(bytes))
+}
+
+fn stats_card<'a>(
+	app: &'a AppModel,
 	traffic: &crate::api::Traffic,
 	_space_xxs: u16,
 	space_xs: u16,
 	space_s: u16,
 	space_m: u16,
 	space_l: u16,
-) -> Element<'static, Message> {
-	let format_bytes = |bytes: u64| -> String {
-		let kb = bytes as f64 / 1024.0;
-		if kb < 1024.0 {
-			format!("{:.1} KB/s", kb)
+) -> Element<'a, Message> {
+	let mut windows = widget::row::with_capacity(ChartWindow::ALL.len()).spacing(space_xs);
+	for window in ChartWindow::ALL {
+		let label = match window {
+			ChartWindow::OneMinute => fl!("window-1m"),
+			ChartWindow::TenMinutes => fl!("window-10m"),
+			ChartWindow::OneHour => fl!("window-1h"),
+		};
+		windows = windows.push(if window == app.chart_window {
+			widget::button::suggested(label)
 		} else {
-			format!("{:.2} MB/s", kb / 1024.0)
-		}
-	};
+			widget::button::standard(label).on_press(Message::SetChartWindow(window))
+		});
+	}
 
-	let header = widget::row::with_capacity(2)
+	let header = widget::row::with_capacity(4)
 		.push(widget::icon::from_name("utilities-system-monitor-symbolic").size(20))
 		.push(widget::text::heading(fl!("statistics")))
+		.push(widget::space::horizontal().width(Length::Fill))
+		.push(windows)
 		.spacing(space_s)
-		.align_y(Alignment::Center);
+		.align_y(Alignment::Center)
+		.width(Length::Fill);
+
+	let chart = widget::container(traffic_chart(&app.session.traffic_history, app.chart_window, 140.0, peak_label))
+		.padding(space_s)
+		.width(Length::Fill)
+		.class(theme::Container::custom(|t| widget::container::Style {
+			background: Some(Background::Color(t.cosmic().bg_component_color().into())),
+			border: Border {
+				radius: 12.0.into(),
+				..Default::default()
+			},
+			..Default::default()
+		}));
 
 	let stat_tile = |icon: &'static str, label: String, value: String| -> Element<'static, Message> {
 		widget::container(
@@ -214,23 +471,54 @@ fn stats_card(
 		.push(stat_tile(
 			"go-up-symbolic",
 			fl!("upload"),
-			format_bytes(traffic.up),
+			format_rate(traffic.up),
 		))
 		.push(stat_tile(
 			"go-down-symbolic",
 			fl!("download"),
-			format_bytes(traffic.down),
+			format_rate(traffic.down),
 		))
 		.spacing(space_s)
 		.width(Length::Fill);
 
-	widget::container(
-		widget::column::with_capacity(2)
-			.push(header)
-			.push(tiles)
-			.spacing(space_s)
-			.width(Length::Fill),
-	)
+	// Session totals and core health, once the slower feeds have reported
+	let mut totals = widget::row::with_capacity(3).spacing(space_s).width(Length::Fill);
+	if let Some(snapshot) = &app.session.connections {
+		totals = totals
+			.push(stat_tile(
+				"network-transmit-receive-symbolic",
+				fl!("session-total"),
+				fl!(
+					"session-total-value",
+					up = format_bytes(snapshot.upload_total),
+					down = format_bytes(snapshot.download_total)
+				),
+			))
+			.push(stat_tile(
+				"network-workgroup-symbolic",
+				fl!("active-connections"),
+				snapshot.connections.len().to_string(),
+			));
+	}
+	if let Some(memory) = &app.session.memory {
+		totals = totals.push(stat_tile(
+			"drive-harddisk-symbolic",
+			fl!("core-memory"),
+			format_bytes(memory.inuse),
+		));
+	}
+
+	let mut column = widget::column::with_capacity(4)
+		.push(header)
+		.push(chart)
+		.push(tiles)
+		.spacing(space_s)
+		.width(Length::Fill);
+	if app.session.connections.is_some() || app.session.memory.is_some() {
+		column = column.push(totals);
+	}
+
+	widget::container(column)
 	.padding(space_l)
 	.width(Length::Fill)
 	.class(theme::Container::Card)
@@ -0,0 +1,178 @@
+// SPDX-License-Identifier: AGPL3.0
+
+use cosmic::{
+	Element,
+	cosmic_theme,
+	iced::{Alignment, Color, Length},
+	theme, widget,
+};
+
+use crate::{
+	api::{LogEntry, LogLevel},
+	app::{AppModel, Message},
+	fl,
+	pages::home::api_error_row,
+};
+
+/// Lines drawn at once; the rest stays in the buffer and the export.
+const LOG_LINES_SHOWN: usize = 500;
+
+pub fn view_logs(app: &AppModel, _space_s: u16) -> Element<'_, Message> {
+	let cosmic_theme::Spacing {
+		space_xxxs,
+		space_xs,
+		space_s,
+		space_m,
+		space_l,
+		..
+	} = theme::active().cosmic().spacing;
+
+	let header = widget::row::with_capacity(5)
+		.push(widget::text::title2(fl!("logs")))
+		.push(widget::space::horizontal().width(Length::Fill))
+		.push(
+			widget::button::standard(fl!("clear"))
+				.on_press_maybe((!app.logs.is_empty()).then_some(Message::ClearLogs)),
+		)
+		.push(
+			widget::button::standard(fl!("export"))
+				.on_press_maybe((!app.logs.is_empty()).then_some(Message::ExportLogs)),
+		)
+		.push(if app.logs_paused {
+			widget::button::suggested(fl!("resume")).on_press(Message::ToggleLogPause)
+		} else {
+			widget::button::standard(fl!("pause")).on_press(Message::ToggleLogPause)
+		})
+		.align_y(Alignment::Center)
+		.spacing(space_s)
+		.width(Length::Fill);
+
+	let mut layout = widget::column::with_capacity(4)
+		.push(header)
+		.spacing(space_m)
+		.width(Length::Fill);
+
+	if let Some(err) = app.controller_error() {
+		layout = layout.push(
+			widget::container(api_error_row(err, space_xs))
+				.padding(space_l)
+				.width(Length::Fill)
+				.class(theme::Container::Card),
+		);
+	}
+
+	let mut levels = widget::row::with_capacity(LogLevel::ALL.len()).spacing(space_xs);
+	for level in LogLevel::ALL {
+		let label = level_label(level);
+		levels = levels.push(if level == app.log_level {
+			widget::button::suggested(label)
+		} else {
+			widget::button::standard(label).on_press(Message::SetLogLevel(level))
+		});
+	}
+
+	let filter = widget::text_input(fl!("filter-logs"), &app.log_filter)
+		.on_input(Message::LogFilterChanged)
+		.padding([space_xs, space_s])
+		.width(Length::Fill);
+
+	let controls = widget::row::with_capacity(2)
+		.push(filter)
+		.push(levels)
+		.spacing(space_s)
+		.align_y(Alignment::Center)
+		.width(Length::Fill);
+
+	let needle = app.log_filter.trim().to_lowercase();
+	let matching: Vec<&LogEntry> = app
+		.logs
+		.iter()
+		.rev()
+		.filter(|entry| needle.is_empty() || entry.payload.to_lowercase().contains(&needle))
+		.collect();
+
+	let status = if app.session.api.is_none() {
+		fl!("logs-core-stopped")
+	} else if app.logs_paused {
+		fl!(
+			"logs-paused",
+			lines = app.logs.len().to_string(),
+			held = app.held_logs.len().to_string()
+		)
+	} else {
+		fl!("logs-following", lines = app.logs.len().to_string())
+	};
+	let mut status_row = widget::row::with_capacity(3)
+		.push(widget::text::caption(status))
+		.push(widget::space::horizontal().width(Length::Fill))
+		.align_y(Alignment::Center)
+		.width(Length::Fill);
+	match &app.log_export {
+		Some(Ok(path)) => {
+			status_row = status_row.push(widget::text::caption(fl!(
+				"logs-exported",
+				path = path.display().to_string()
+			)));
+		}
+		Some(Err(err)) => {
+			status_row = status_row.push(widget::text::caption(fl!("logs-export-failed", reason = err.clone())));
+		}
+		None => {}
+	}
+
+	let mut lines = widget::column::with_capacity(matching.len().min(LOG_LINES_SHOWN))
+		.spacing(space_xxxs)
+		.width(Length::Fill);
+	for entry in matching.iter().take(LOG_LINES_SHOWN) {
+		lines = lines.push(log_line(entry, space_s));
+	}
+
+	layout
+		.push(
+			widget::container(
+				widget::column::with_capacity(4)
+					.push(controls)
+					.push(status_row)
+					.push(widget::divider::horizontal::default())
+					.push(lines)
+					.spacing(space_s)
+					.width(Length::Fill),
+			)
+			.padding(space_l)
+			.width(Length::Fill)
+			.class(theme::Container::Card),
+		)
+		.into()
+}
+
+fn level_label(level: LogLevel) -> String {
+	match level {
+		LogLevel::Debug => fl!("log-debug"),
+		LogLevel::Info => fl!("log-info"),
+		LogLevel::Warning => fl!("log-warning"),
+		LogLevel::Error => fl!("log-error"),
+		LogLevel::Silent => fl!("log-silent"),
+	}
+}
+
+fn log_line(entry: &LogEntry, space_s: u16) -> Element<'static, Message> {
+	let color: Option<fn(&cosmic::Theme) -> Color> = match entry.level.as_str() {
+		"error" => Some(|t| t.cosmic().destructive_color().into()),
+		"warning" => Some(|t| t.cosmic().warning_color().into()),
+		_ => None,
+	};
+
+	let level = widget::container(widget::text::monotext(entry.level.to_uppercase()))
+		.width(Length::Fixed(72.0))
+		.class(theme::Container::custom(move |t| widget::container::Style {
+			text_color: color.map(|color| color(t)),
+			..Default::default()
+		}));
+
+	widget::row::with_capacity(2)
+		.push(level)
+		.push(widget::text::monotext(entry.payload.clone()).width(Length::Fill))
+		.spacing(space_s)
+		.width(Length::Fill)
+		.into()
+}
@@ -8,10 +8,23 @@ use cosmic::{
 };
 
 use crate::{
-	app::{AppModel, Message},
-	fl,
+	api::SubscriptionInfo,
+	app::{AppModel, Message, ProfileEdit, ProfileEditField, RemoteFormField, RemoteProfileForm},
+	fl, overrides,
+	pages::{
+		home::{api_error_row, config_issue_text},
+		providers::subscription_text,
+	},
+	preflight::ConfigIssue,
+	profiles::{self, DEFAULT_USER_AGENT, Profile, RemoteSource},
 };
 
+/// Share of a subscription's quota after which it is flagged.
+const USAGE_WARNING_RATIO: f32 = 0.9;
+
+/// Days before a subscription expires that it is flagged.
+const EXPIRY_WARNING_DAYS: i64 = 7;
+
 pub fn view_profile(app: &AppModel, _space_s: u16) -> Element<'_, Message> {
 	let cosmic_theme::Spacing {
 		space_xxs,
@@ -29,6 +42,10 @@ pub fn view_profile(app: &AppModel, _space_s: u16) -> Element<'_, Message> {
 				.spacing(space_xxs / 2),
 		)
 		.push(widget::space::horizontal().width(Length::Fill))
+		.push(
+			widget::button::standard(fl!("add-subscription"))
+				.on_press_maybe(app.remote_form.is_none().then_some(Message::OpenRemoteForm)),
+		)
 		.push(widget::button::standard(fl!("reload-config")).on_press(Message::ReloadConfig))
 		.align_y(Alignment::Center)
 		.spacing(space_s)
@@ -36,32 +53,54 @@ pub fn view_profile(app: &AppModel, _space_s: u16) -> Element<'_, Message> {
 
 	let active_card = active_profile_card(app, space_xxs, space_xs, space_s, space_l);
 
-	let mut layout = widget::column::with_capacity(3)
+	let mut layout = widget::column::with_capacity(4)
 		.push(header)
-		.push(active_card)
 		.spacing(space_m)
 		.width(Length::Fill);
 
-	if app.profiles.is_empty() {
+	if let Some((file, issue)) = &app.profile_issue {
+		let name = app.profiles.get(file).map_or(file.as_str(), |profile| profile.name.as_str());
+		layout = layout.push(issue_card(name, issue, space_xxs, space_s, space_l));
+	}
+
+	if let Some(err) = app.controller_error() {
+		layout = layout.push(
+			widget::container(api_error_row(err, space_xs))
+				.padding(space_l)
+				.width(Length::Fill)
+				.class(theme::Container::Card),
+		);
+	}
+
+	layout = layout.push(active_card);
+
+	if let Some(form) = &app.remote_form {
+		layout = layout.push(remote_form(form, space_xs, space_s, space_l));
+	}
+
+	if app.profiles.profiles.is_empty() {
 		layout = layout.push(empty_profiles(space_s, space_m, space_l));
 	} else {
 		layout = layout.push(profile_list(app, space_xxs, space_xs, space_s, space_l));
 	}
 
-	layout.into()
+	layout.push(override_card(app, space_xs, space_s, space_l)).into()
 }
 
 fn active_profile_card(
 	app: &AppModel,
-	_space_xxs: u16,
-	_space_xs: u16,
+	space_xxs: u16,
+	space_xs: u16,
 	space_s: u16,
 	space_l: u16,
 ) -> Element<'_, Message> {
 	let active = app.config.active_profile.as_deref();
-	let label = active
-		.map(|s| s.to_string())
-		.unwrap_or_else(|| fl!("no-profile"));
+	let profile = active.and_then(|file| app.profiles.get(file));
+	let label = match (profile, active) {
+		(Some(profile), _) => profile.name.clone(),
+		(None, Some(file)) => file.to_string(),
+		(None, None) => fl!("no-profile"),
+	};
 
 	let icon_name = if active.is_some() {
 		"emblem-default-symbolic"
@@ -88,9 +127,93 @@ fn active_profile_card(
 		.push(widget::text::title3(label))
 		.spacing(2);
 
+	let mut column = widget::column::with_capacity(2)
+		.push(
+			widget::row::with_capacity(2)
+				.push(icon_disc)
+				.push(text_col)
+				.spacing(space_s)
+				.align_y(Alignment::Center)
+				.width(Length::Fill),
+		)
+		.spacing(space_s)
+		.width(Length::Fill);
+
+	let userinfo = profile.and_then(Profile::remote).and_then(RemoteSource::quota);
+	if let Some(info) = userinfo {
+		column = column.push(subscription_usage(info, space_xxs, space_xs));
+	}
+
+	widget::container(column)
+		.padding(space_l)
+		.width(Length::Fill)
+		.class(theme::Container::Card)
+		.into()
+}
+
+/// Quota bar of a subscription with its usage, expiry and any warning.
+fn subscription_usage(info: SubscriptionInfo, space_xxs: u16, space_xs: u16) -> Element<'static, Message> {
+	let mut column = widget::column::with_capacity(3).spacing(space_xxs).width(Length::Fill);
+	if let Some(ratio) = info.used_ratio() {
+		column = column.push(cosmic::iced::widget::progress_bar(0.0..=1.0, ratio.min(1.0)));
+	}
+	column = column.push(widget::text::caption(subscription_text(&info)));
+	if let Some(warning) = subscription_warning(&info) {
+		column = column.push(warning_row(warning, space_xs));
+	}
+	column.into()
+}
+
+/// Why a subscription needs attention soon, if it does.
+fn subscription_warning(info: &SubscriptionInfo) -> Option<String> {
+	if info.expire > 0 {
+		let days_left = (info.expire - profiles::unix_now()).div_euclid(24 * 3600);
+		if days_left < 0 {
+			return Some(fl!("subscription-expired"));
+		}
+		if days_left < EXPIRY_WARNING_DAYS {
+			return Some(fl!("subscription-expires-soon", days = days_left.to_string()));
+		}
+	}
+	match info.used_ratio() {
+		Some(ratio) if ratio >= 1.0 => Some(fl!("subscription-used-up")),
+		Some(ratio) if ratio >= USAGE_WARNING_RATIO => Some(fl!(
+			"subscription-nearly-used",
+			percent = format!("{:.0}", ratio * 100.0)
+		)),
+		_ => None,
+	}
+}
+
+fn warning_row(warning: String, space_xs: u16) -> Element<'static, Message> {
+	let row = widget::row::with_capacity(2)
+		.push(widget::icon::from_name("dialog-warning-symbolic").size(16))
+		.push(widget::text::caption(warning))
+		.spacing(space_xs)
+		.align_y(Alignment::Center);
+
+	widget::container(row)
+		.class(theme::Container::custom(|t| {
+			let warning = t.cosmic().warning_color();
+			widget::container::Style {
+				text_color: Some(warning.into()),
+				icon_color: Some(warning.into()),
+				..Default::default()
+			}
+		}))
+		.into()
+}
+
+fn issue_card(profile: &str, issue: &ConfigIssue, space_xxs: u16, space_s: u16, space_l: u16) -> Element<'static, Message> {
+	let text_col = widget::column::with_capacity(2)
+		.push(widget::text::heading(fl!("profile-rejected", profile = profile.to_string())))
+		.push(widget::text::body(config_issue_text(issue)))
+		.spacing(space_xxs)
+		.width(Length::Fill);
+
 	widget::container(
 		widget::row::with_capacity(2)
-			.push(icon_disc)
+			.push(widget::icon::from_name("dialog-error-symbolic").size(24))
 			.push(text_col)
 			.spacing(space_s)
 			.align_y(Alignment::Center)
@@ -98,7 +221,16 @@ fn active_profile_card(
 	)
 	.padding(space_l)
 	.width(Length::Fill)
-	.class(theme::Container::Card)
+	.class(theme::Container::custom(|t| {
+		let cosmic = t.cosmic();
+		widget::container::Style {
+			background: Some(Background::Color(cosmic.bg_component_color().into())),
+			text_color: Some(cosmic.on_bg_component_color().into()),
+			icon_color: Some(cosmic.destructive_color().into()),
+			border: Border { radius: 12.0.into(), ..Default::default() },
+			..Default::default()
+		}
+	}))
 	.into()
 }
 
@@ -133,7 +265,7 @@ fn empty_profiles(space_s: u16, space_m: u16, space_l: u16) -> Element<'static,
 fn profile_list<'a>(
 	app: &'a AppModel,
 	_space_xxs: u16,
-	_space_xs: u16,
+	space_xs: u16,
 	space_s: u16,
 	space_l: u16,
 ) -> Element<'a, Message> {
@@ -141,41 +273,296 @@ fn profile_list<'a>(
 		.push(widget::icon::from_name("folder-open-symbolic").size(20))
 		.push(widget::text::heading(fl!("available-profiles")))
 		.push(widget::space::horizontal().width(Length::Fill))
-		.push(widget::text::caption(format!("{}", app.profiles.len())))
+		.push(widget::text::caption(format!("{}", app.profiles.profiles.len())))
 		.spacing(space_s)
 		.align_y(Alignment::Center)
 		.width(Length::Fill);
 
 	let mut section = widget::settings::section();
-
-	for profile in &app.profiles {
-		let is_active = app.config.active_profile.as_deref() == Some(profile.as_str());
-
-		let indicator = widget::icon::from_name(if is_active {
-			"emblem-default-symbolic"
-		} else {
-			"folder-symbolic"
-		})
-		.size(20);
-
-		let item = widget::settings::item::builder(profile.clone()).icon(indicator);
-
-		let row = if is_active {
-			item.control(widget::text::caption(fl!("selected")))
-		} else {
-			item.control(
-				widget::button::text(fl!("select"))
-					.on_press(Message::SelectProfile(profile.clone())),
-			)
+	for profile in &app.profiles.profiles {
+		let row = match &app.profile_edit {
+			Some(edit) if edit.file == profile.file => profile_edit_row(edit, space_xs, space_s),
+			_ => profile_row(app, profile, space_xs),
 		};
-
 		section = section.add(row);
 	}
 
+	let mut column = widget::column::with_capacity(3)
+		.push(header)
+		.push(Element::from(section))
+		.spacing(space_s)
+		.width(Length::Fill);
+	if let Some(err) = &app.profile_error {
+		column = column.push(widget::text::caption(err.clone()));
+	}
+
+	widget::container(column)
+		.padding(space_l)
+		.width(Length::Fill)
+		.class(theme::Container::Card)
+		.into()
+}
+
+/// The override merged onto every profile, with the generated config the
+/// core runs when it is shown.
+fn override_card(app: &AppModel, space_xs: u16, space_s: u16, space_l: u16) -> Element<'_, Message> {
+	let header = widget::row::with_capacity(3)
+		.push(widget::icon::from_name("document-edit-symbolic").size(20))
+		.push(widget::text::heading(fl!("profile-override")))
+		.spacing(space_s)
+		.align_y(Alignment::Center)
+		.width(Length::Fill);
+
+	let merged_button = if app.merged_config.is_some() {
+		widget::button::standard(fl!("hide-merged-config")).on_press(Message::HideMergedConfig)
+	} else {
+		widget::button::standard(fl!("show-merged-config")).on_press(Message::ShowMergedConfig)
+	};
+	let item = widget::settings::item::builder(fl!("override-file"))
+		.description(overrides::path().display().to_string())
+		.control(
+			widget::row::with_capacity(2)
+				.push(widget::button::standard(fl!("edit-override")).on_press(Message::EditOverride))
+				.push(merged_button)
+				.spacing(space_xs)
+				.align_y(Alignment::Center),
+		);
+
+	let mut column = widget::column::with_capacity(5)
+		.push(header)
+		.push(widget::text::caption(fl!("override-description")))
+		.push(Element::from(widget::settings::section().add(item)))
+		.spacing(space_s)
+		.width(Length::Fill);
+	if let Some(err) = &app.override_error {
+		column = column.push(widget::text::caption(fl!("override-open-failed", reason = err.clone())));
+	}
+	match &app.merged_config {
+		Some(Ok(merged)) => {
+			column = column.push(
+				widget::container(widget::text::monotext(merged.clone()).width(Length::Fill))
+					.padding(space_s)
+					.width(Length::Fill)
+					.class(theme::Container::custom(|t| {
+						let cosmic = t.cosmic();
+						widget::container::Style {
+							background: Some(Background::Color(cosmic.bg_component_color().into())),
+							text_color: Some(cosmic.on_bg_component_color().into()),
+							border: Border { radius: 8.0.into(), ..Default::default() },
+							..Default::default()
+						}
+					})),
+			);
+		}
+		Some(Err(err)) => {
+			column = column.push(widget::text::caption(fl!("merged-config-failed", reason = err.clone())));
+		}
+		None => {}
+	}
+
+	widget::container(column)
+		.padding(space_l)
+		.width(Length::Fill)
+		.class(theme::Container::Card)
+		.into()
+}
+
+fn profile_row<'a>(app: &'a AppModel, profile: &'a Profile, space_xs: u16) -> Element<'a, Message> {
+	let file = &profile.file;
+	let is_active = app.config.active_profile.as_deref() == Some(file.as_str());
+	let remote = profile.remote();
+
+	let indicator = widget::icon::from_name(if is_active {
+		"emblem-default-symbolic"
+	} else if remote.is_some() {
+		"folder-remote-symbolic"
+	} else {
+		"folder-symbolic"
+	})
+	.size(20);
+
+	let item = widget::settings::item::builder(profile.name.clone())
+		.description(profile_status(app, profile))
+		.icon(indicator);
+
+	let mut controls = widget::row::with_capacity(6)
+		.spacing(space_xs)
+		.align_y(Alignment::Center);
+
+	if app.deleting_profile.as_ref() == Some(file) {
+		// It may have been selected while the confirmation was open
+		if is_active {
+			controls = controls
+				.push(widget::text::caption(fl!("profile-delete-active")))
+				.push(widget::button::text(fl!("cancel")).on_press(Message::CancelDeleteProfile));
+			return item.control(controls).into();
+		}
+		controls = controls
+			.push(widget::text::caption(fl!("delete-profile-confirm")))
+			.push(widget::button::text(fl!("cancel")).on_press(Message::CancelDeleteProfile))
+			.push(widget::button::destructive(fl!("delete")).on_press(Message::ConfirmDeleteProfile));
+		return item.control(controls).into();
+	}
+
+	if remote.and_then(RemoteSource::quota).is_some_and(|info| subscription_warning(&info).is_some()) {
+		controls = controls.push(widget::icon::from_name("dialog-warning-symbolic").size(16));
+	}
+	if remote.is_some() {
+		let refreshing = app.refreshing_profiles.contains(file);
+		controls = controls.push(
+			widget::button::text(if refreshing { fl!("updating") } else { fl!("update") })
+				.on_press_maybe((!refreshing).then(|| Message::RefreshProfile(file.clone()))),
+		);
+	}
+	controls = controls
+		.push(
+			widget::button::icon(widget::icon::from_name("document-edit-symbolic"))
+				.on_press(Message::EditProfile(file.clone())),
+		)
+		.push(
+			widget::button::icon(widget::icon::from_name("edit-copy-symbolic"))
+				.on_press_maybe(profile.is_downloaded().then(|| Message::DuplicateProfile(file.clone()))),
+		)
+		// The running profile cannot be deleted from under the core
+		.push(
+			widget::button::icon(widget::icon::from_name("user-trash-symbolic"))
+				.on_press_maybe((!is_active).then(|| Message::DeleteProfile(file.clone()))),
+		);
+	controls = if is_active {
+		controls.push(widget::text::caption(fl!("selected")))
+	} else {
+		controls.push(
+			widget::button::text(fl!("select"))
+				.on_press_maybe(profile.is_downloaded().then(|| Message::SelectProfile(file.clone()))),
+		)
+	};
+
+	item.control(controls).into()
+}
+
+fn profile_edit_row(edit: &ProfileEdit, space_xs: u16, space_s: u16) -> Element<'_, Message> {
+	let name = widget::text_input(fl!("profile-name"), &edit.name)
+		.on_input(|value| Message::ProfileEditChanged(ProfileEditField::Name, value))
+		.on_submit(|_| Message::SaveProfileEdit)
+		.padding([space_xs, space_s])
+		.width(Length::FillPortion(1));
+	let notes = widget::text_input(fl!("profile-notes-placeholder"), &edit.notes)
+		.on_input(|value| Message::ProfileEditChanged(ProfileEditField::Notes, value))
+		.on_submit(|_| Message::SaveProfileEdit)
+		.padding([space_xs, space_s])
+		.width(Length::FillPortion(2));
+
+	let row = widget::row::with_capacity(4)
+		.push(name)
+		.push(notes)
+		.push(widget::button::standard(fl!("cancel")).on_press(Message::CancelProfileEdit))
+		.push(widget::button::suggested(fl!("save")).on_press(Message::SaveProfileEdit))
+		.spacing(space_xs)
+		.align_y(Alignment::Center)
+		.width(Length::Fill);
+
+	widget::settings::item::builder(format!("{}.yaml", edit.file))
+		.flex_control(row)
+		.into()
+}
+
+/// Where a profile comes from and how its last download went, when it was
+/// used, its notes, and for subscriptions the quota and any warning about it,
+/// one per line.
+fn profile_status(app: &AppModel, profile: &Profile) -> String {
+	let mut lines = Vec::with_capacity(5);
+	let updated = match profile.updated_at.filter(|_| profile.is_downloaded()).and_then(format_timestamp) {
+		Some(updated) => fl!("profile-updated-at", date = updated),
+		None => fl!("profile-not-downloaded"),
+	};
+	match profile.remote() {
+		None => lines.push(fl!(
+			"local-profile-status",
+			file = format!("{}.yaml", profile.file),
+			updated = updated
+		)),
+		Some(_) if app.profile_fetch_errors.contains_key(&profile.file) => lines.push(fl!(
+			"profile-download-failed",
+			reason = app.profile_fetch_errors[&profile.file].clone()
+		)),
+		Some(remote) if remote.refresh_hours > 0 => lines.push(fl!(
+			"remote-profile-status-auto",
+			updated = updated,
+			hours = remote.refresh_hours.to_string()
+		)),
+		Some(_) => lines.push(fl!("remote-profile-status", updated = updated)),
+	}
+	if let Some(used) = profile.last_used_at.and_then(format_timestamp) {
+		lines.push(fl!("profile-last-used", date = used));
+	}
+	if !profile.notes.is_empty() {
+		lines.push(profile.notes.clone());
+	}
+	if let Some(info) = profile.remote().and_then(RemoteSource::quota) {
+		lines.push(subscription_text(&info));
+		lines.extend(subscription_warning(&info));
+	}
+	lines.join("\n")
+}
+
+fn format_timestamp(unix: i64) -> Option<String> {
+	let utc = time::OffsetDateTime::from_unix_timestamp(unix).ok()?;
+	let local = time::UtcOffset::current_local_offset()
+		.map(|offset| utc.to_offset(offset))
+		.unwrap_or(utc);
+	Some(format!(
+		"{}-{:02}-{:02} {:02}:{:02}",
+		local.year(),
+		u8::from(local.month()),
+		local.day(),
+		local.hour(),
+		local.minute()
+	))
+}
+
+fn remote_form(form: &RemoteProfileForm, space_xs: u16, space_s: u16, space_l: u16) -> Element<'_, Message> {
+	let input = |placeholder, value, field| form_input(placeholder, value, field, space_xs, space_s);
+
+	let section = widget::settings::section()
+		.title(fl!("add-subscription"))
+		.add(
+			widget::settings::item::builder(fl!("profile-name"))
+				.flex_control(input(fl!("profile-name-placeholder"), &form.name, RemoteFormField::Name)),
+		)
+		.add(
+			widget::settings::item::builder(fl!("subscription-url"))
+				.flex_control(input(fl!("subscription-url-placeholder"), &form.url, RemoteFormField::Url)),
+		)
+		.add(
+			widget::settings::item::builder(fl!("user-agent"))
+				.flex_control(input(DEFAULT_USER_AGENT.to_string(), &form.user_agent, RemoteFormField::UserAgent)),
+		)
+		.add(
+			widget::settings::item::builder(fl!("refresh-interval")).flex_control(input(
+				fl!("refresh-interval-placeholder"),
+				&form.refresh_hours,
+				RemoteFormField::RefreshHours,
+			)),
+		);
+
+	let mut actions = widget::row::with_capacity(4)
+		.align_y(Alignment::Center)
+		.spacing(space_xs)
+		.width(Length::Fill);
+	if let Some(err) = &form.error {
+		actions = actions.push(widget::text::body(err.clone()).class(theme::Text::Color(
+			theme::active().cosmic().destructive_color().into(),
+		)));
+	}
+	actions = actions
+		.push(widget::space::horizontal().width(Length::Fill))
+		.push(widget::button::standard(fl!("cancel")).on_press(Message::CancelRemoteForm))
+		.push(widget::button::suggested(fl!("add")).on_press(Message::AddRemoteProfile));
+
 	widget::container(
 		widget::column::with_capacity(2)
-			.push(header)
 			.push(Element::from(section))
+			.push(actions)
 			.spacing(space_s)
 			.width(Length::Fill),
 	)
@@ -184,3 +571,18 @@ fn profile_list<'a>(
 	.class(theme::Container::Card)
 	.into()
 }
+
+fn form_input<'a>(
+	placeholder: String,
+	value: &'a str,
+	field: RemoteFormField,
+	space_xs: u16,
+	space_s: u16,
+) -> Element<'a, Message> {
+	widget::text_input(placeholder, value)
+		.on_input(move |value| Message::RemoteFormChanged(field, value))
+		.on_submit(|_| Message::AddRemoteProfile)
+		.padding([space_xs, space_s])
+		.width(Length::Fill)
+		.into()
+}
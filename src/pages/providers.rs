@@ -0,0 +1,231 @@
+// SPDX-License-Identifier: AGPL3.0
+
+use cosmic::{
+	Element,
+	cosmic_theme,
+	iced::{Alignment, Background, Border, Length},
+	theme, widget,
+};
+
+use crate::{
+	api::{ProviderKind, ProxyProvider, RuleProvider, SubscriptionInfo},
+	app::{AppModel, Message},
+	core_info::CoreFeature,
+	fl,
+	pages::{connections::format_bytes, home::api_error_row},
+};
+
+pub fn view_providers(app: &AppModel, _space_s: u16) -> Element<'_, Message> {
+	let cosmic_theme::Spacing {
+		space_xxs,
+		space_xs,
+		space_s,
+		space_m,
+		space_l,
+		..
+	} = theme::active().cosmic().spacing;
+
+	let header = widget::row::with_capacity(3)
+		.push(widget::text::title2(fl!("providers")))
+		.push(widget::space::horizontal().width(Length::Fill))
+		.push(
+			widget::button::standard(fl!("refresh"))
+				.on_press_maybe(app.session.api.as_ref().map(|_| Message::RefreshProviders)),
+		)
+		.align_y(Alignment::Center)
+		.spacing(space_s)
+		.width(Length::Fill);
+
+	let mut layout = widget::column::with_capacity(4)
+		.push(header)
+		.spacing(space_m)
+		.width(Length::Fill);
+
+	if let Some(err) = app.controller_error() {
+		layout = layout.push(
+			widget::container(api_error_row(err, space_xs))
+				.padding(space_l)
+				.width(Length::Fill)
+				.class(theme::Container::Card),
+		);
+	}
+
+	let proxy_providers: Vec<&ProxyProvider> = app
+		.session
+		.proxy_providers
+		.iter()
+		.flatten()
+		.filter(|provider| !provider.is_inline())
+		.collect();
+	let rule_providers: Vec<&RuleProvider> = app.session.rule_providers.iter().flatten().collect();
+
+	if app.session.api.is_none() || (proxy_providers.is_empty() && rule_providers.is_empty()) {
+		return layout.push(empty_providers(app, space_s, space_m, space_l)).into();
+	}
+
+	if !proxy_providers.is_empty() {
+		let mut section = widget::settings::section().title(fl!("proxy-providers"));
+		for provider in proxy_providers {
+			section = section.add(proxy_provider_item(app, provider, space_xxs, space_xs));
+		}
+		layout = layout.push(Element::from(section));
+	}
+
+	if !rule_providers.is_empty() {
+		let mut section = widget::settings::section().title(fl!("rule-providers"));
+		for provider in rule_providers {
+			section = section.add(rule_provider_item(app, provider, space_xs));
+		}
+		layout = layout.push(Element::from(section));
+	} else if !app.supports(CoreFeature::RuleProviders) {
+		layout = layout.push(widget::text::caption(fl!("rule-providers-unsupported")));
+	}
+
+	layout.into()
+}
+
+fn empty_providers(app: &AppModel, space_s: u16, space_m: u16, space_l: u16) -> Element<'static, Message> {
+	let icon = widget::container(widget::icon::from_name("folder-download-symbolic").size(48))
+		.padding(space_m)
+		.class(theme::Container::custom(|t| {
+			let cosmic = t.cosmic();
+			widget::container::Style {
+				background: Some(Background::Color(cosmic.bg_component_color().into())),
+				icon_color: Some(cosmic.on_bg_component_color().into()),
+				border: Border { radius: 64.0.into(), ..Default::default() },
+				..Default::default()
+			}
+		}));
+
+	let description = if app.session.api.is_none() {
+		fl!("providers-core-stopped")
+	} else if app.session.proxy_providers.is_none() {
+		fl!("providers-loading")
+	} else {
+		fl!("no-providers")
+	};
+
+	let column = widget::column::with_capacity(2)
+		.align_x(Alignment::Center)
+		.spacing(space_s)
+		.width(Length::Fill)
+		.push(icon)
+		.push(widget::text::body(description));
+
+	widget::container(column)
+		.padding(space_l + space_m)
+		.width(Length::Fill)
+		.class(theme::Container::Card)
+		.into()
+}
+
+fn proxy_provider_item<'a>(
+	app: &AppModel,
+	provider: &'a ProxyProvider,
+	space_xxs: u16,
+	space_xs: u16,
+) -> Element<'a, Message> {
+	let alive = provider
+		.proxies
+		.iter()
+		.filter(|proxy| proxy.last_delay().is_some_and(|delay| delay > 0))
+		.count();
+
+	let mut description = widget::column::with_capacity(2)
+		.push(widget::text::caption(fl!(
+			"proxy-provider-summary",
+			vehicle = provider.vehicle_type.clone(),
+			count = provider.proxies.len().to_string(),
+			alive = alive.to_string(),
+			updated = updated_at(provider.updated_at.as_deref())
+		)))
+		.spacing(space_xxs);
+	if let Some(info) = &provider.subscription_info {
+		description = description.push(widget::text::caption(subscription_text(info)));
+	}
+
+	let busy = app.session.busy_providers.contains(&(ProviderKind::Proxy, provider.name.clone()));
+	let actions = widget::row::with_capacity(2)
+		.push(
+			widget::button::standard(fl!("health-check"))
+				.on_press_maybe((!busy).then(|| Message::HealthCheckProvider(provider.name.clone()))),
+		)
+		.push(
+			widget::button::standard(if busy { fl!("updating") } else { fl!("update") }).on_press_maybe(
+				(!busy).then(|| Message::UpdateProvider(ProviderKind::Proxy, provider.name.clone())),
+			),
+		)
+		.spacing(space_xs)
+		.align_y(Alignment::Center);
+
+	widget::settings::item::builder(provider.name.clone())
+		.icon(widget::icon::from_name("network-server-symbolic").size(16))
+		.control(
+			widget::row::with_capacity(2)
+				.push(description)
+				.push(actions)
+				.spacing(space_xs)
+				.align_y(Alignment::Center),
+		)
+		.into()
+}
+
+fn rule_provider_item<'a>(app: &AppModel, provider: &'a RuleProvider, space_xs: u16) -> Element<'a, Message> {
+	let busy = app.session.busy_providers.contains(&(ProviderKind::Rule, provider.name.clone()));
+
+	let description = widget::text::caption(fl!(
+		"rule-provider-summary",
+		vehicle = provider.vehicle_type.clone(),
+		behavior = provider.behavior.clone(),
+		count = provider.rule_count.to_string(),
+		updated = updated_at(provider.updated_at.as_deref())
+	));
+
+	widget::settings::item::builder(provider.name.clone())
+		.icon(widget::icon::from_name("view-list-symbolic").size(16))
+		.control(
+			widget::row::with_capacity(2)
+				.push(description)
+				.push(
+					widget::button::standard(if busy { fl!("updating") } else { fl!("update") }).on_press_maybe(
+						(!busy).then(|| Message::UpdateProvider(ProviderKind::Rule, provider.name.clone())),
+					),
+				)
+				.spacing(space_xs)
+				.align_y(Alignment::Center),
+		)
+		.into()
+}
+
+/// `2024-05-01T12:03:44.123+08:00` becomes `2024-05-01 12:03`.
+fn updated_at(timestamp: Option<&str>) -> String {
+	match timestamp {
+		// Go's zero time means the provider was never fetched
+		Some(ts) if !ts.is_empty() && !ts.starts_with("0001-") => {
+			ts.chars().take(16).collect::<String>().replace('T', " ")
+		}
+		_ => fl!("never"),
+	}
+}
+
+/// Used and total traffic, plus the expiry date when there is one.
+pub fn subscription_text(info: &SubscriptionInfo) -> String {
+	let used = format_bytes(info.used());
+	let quota = if info.total > 0 {
+		fl!("subscription-usage", used = used, total = format_bytes(info.total))
+	} else {
+		fl!("subscription-usage-unlimited", used = used)
+	};
+	match expiry_date(info.expire) {
+		Some(date) => fl!("subscription-expires", quota = quota, date = date),
+		None => quota,
+	}
+}
+
+fn expiry_date(expire: i64) -> Option<String> {
+	if expire <= 0 {
+		return None;
+	}
+	let date = time::OffsetDateTime::from_unix_timestamp(expire).ok()?.date();
+	Some(format!("{}-{:02}-{:02}", date.year(), u8::from(date.month()), date.day()))
+}
@@ -0,0 +1,226 @@
+// SPDX-License-Identifier: AGPL3.0
+
+use cosmic::{
+	Element,
+	cosmic_theme,
+	iced::{Alignment, Background, Border, Color, Length},
+	theme, widget,
+};
+
+use crate::{
+	api::ProxySummary,
+	app::{AppModel, Delay, Message},
+	fl,
+	pages::home::api_error_row,
+};
+
+/// Latencies below this many milliseconds show as good.
+const FAST_DELAY_MS: u64 = 200;
+/// Latencies below this many milliseconds show as usable, above as poor.
+const SLOW_DELAY_MS: u64 = 500;
+
+pub fn view_proxies(app: &AppModel, _space_s: u16) -> Element<'_, Message> {
+	let cosmic_theme::Spacing {
+		space_xxs,
+		space_xs,
+		space_s,
+		space_m,
+		space_l,
+		..
+	} = theme::active().cosmic().spacing;
+
+	let can_test = app.session.api.is_some() && app.session.proxies.is_some();
+	let header = widget::row::with_capacity(4)
+		.push(widget::text::title2(fl!("proxies")))
+		.push(widget::space::horizontal().width(Length::Fill))
+		.push(widget::button::standard(fl!("test-all")).on_press_maybe(can_test.then_some(Message::TestAllDelays)))
+		.push(
+			widget::button::standard(fl!("refresh"))
+				.on_press_maybe(app.session.api.as_ref().map(|_| Message::RefreshProxies)),
+		)
+		.align_y(Alignment::Center)
+		.spacing(space_s)
+		.width(Length::Fill);
+
+	let mut layout = widget::column::with_capacity(3)
+		.push(header)
+		.spacing(space_m)
+		.width(Length::Fill);
+
+	if let Some(err) = app.controller_error() {
+		layout = layout.push(
+			widget::container(api_error_row(err, space_xs))
+				.padding(space_l)
+				.width(Length::Fill)
+				.class(theme::Container::Card),
+		);
+	}
+
+	let groups = app.session.proxies.as_ref().map(|p| p.groups()).unwrap_or_default();
+	if app.session.api.is_none() || groups.is_empty() {
+		return layout.push(empty_proxies(app, space_s, space_m, space_l)).into();
+	}
+
+	for (name, group) in groups {
+		layout = layout.push(group_card(app, name, group, space_xxs, space_xs, space_s, space_l));
+	}
+
+	layout.into()
+}
+
+fn empty_proxies(app: &AppModel, space_s: u16, space_m: u16, space_l: u16) -> Element<'static, Message> {
+	let icon = widget::container(widget::icon::from_name("network-workgroup-symbolic").size(48))
+		.padding(space_m)
+		.class(theme::Container::custom(|t| {
+			let cosmic = t.cosmic();
+			widget::container::Style {
+				background: Some(Background::Color(cosmic.bg_component_color().into())),
+				icon_color: Some(cosmic.on_bg_component_color().into()),
+				border: Border { radius: 64.0.into(), ..Default::default() },
+				..Default::default()
+			}
+		}));
+
+	let description = if app.session.api.is_none() {
+		fl!("proxies-core-stopped")
+	} else {
+		fl!("no-proxy-groups")
+	};
+
+	let column = widget::column::with_capacity(2)
+		.align_x(Alignment::Center)
+		.spacing(space_s)
+		.width(Length::Fill)
+		.push(icon)
+		.push(widget::text::body(description));
+
+	widget::container(column)
+		.padding(space_l + space_m)
+		.width(Length::Fill)
+		.class(theme::Container::Card)
+		.into()
+}
+
+fn group_card<'a>(
+	app: &'a AppModel,
+	name: &'a str,
+	group: &'a ProxySummary,
+	space_xxs: u16,
+	space_xs: u16,
+	space_s: u16,
+	space_l: u16,
+) -> Element<'a, Message> {
+	let expanded = app.expanded_groups.contains(name);
+	let members = group.all.as_deref().unwrap_or_default();
+	let current = group.now.as_deref().unwrap_or_default();
+
+	let summary = widget::column::with_capacity(2)
+		.push(widget::text::heading(name))
+		.push(widget::text::caption(fl!(
+			"proxy-group-summary",
+			kind = group.proxy_type.clone().unwrap_or_default(),
+			current = current.to_string(),
+			count = members.len().to_string()
+		)))
+		.spacing(space_xxs);
+
+	let header = widget::row::with_capacity(4)
+		.push(summary)
+		.push(widget::space::horizontal().width(Length::Fill))
+		.push(widget::button::text(fl!("test-latency")).on_press(Message::TestGroupDelay(name.to_string())))
+		.push(
+			widget::button::text(if expanded { fl!("hide") } else { fl!("show") })
+				.on_press(Message::ToggleProxyGroup(name.to_string())),
+		)
+		.spacing(space_s)
+		.align_y(Alignment::Center)
+		.width(Length::Fill);
+
+	let mut column = widget::column::with_capacity(2)
+		.push(header)
+		.spacing(space_xs)
+		.width(Length::Fill);
+
+	if expanded {
+		let mut section = widget::settings::section();
+		for member in members {
+			let is_current = member == current;
+			let indicator = widget::icon::from_name(if is_current {
+				"emblem-default-symbolic"
+			} else {
+				"network-server-symbolic"
+			})
+			.size(16);
+
+			let mut control = widget::row::with_capacity(2)
+				.push(delay_badge(app, member, space_xxs, space_xs))
+				.spacing(space_s)
+				.align_y(Alignment::Center);
+			if is_current {
+				control = control.push(widget::text::caption(fl!("selected")));
+			} else if group.is_selectable() {
+				control = control.push(widget::button::text(fl!("select")).on_press(Message::SelectProxy {
+					group: name.to_string(),
+					name: member.clone(),
+				}));
+			}
+			// URLTest/Fallback/LoadBalance pick members themselves, so they get no button
+
+			section = section.add(widget::settings::item::builder(member.clone()).icon(indicator).control(control));
+		}
+		column = column.push(Element::from(section));
+	}
+
+	widget::container(column)
+		.padding(space_l)
+		.width(Length::Fill)
+		.class(theme::Container::Card)
+		.into()
+}
+
+/// Latency of `name` as a pill; clicking it tests the proxy again. Falls back
+/// to the core's own history until we have tested it ourselves.
+fn delay_badge<'a>(app: &AppModel, name: &str, space_xxs: u16, space_xs: u16) -> Element<'a, Message> {
+	let delay = app.session.delays.get(name).copied().or_else(|| {
+		let proxy = app.session.proxies.as_ref()?.proxies.get(name)?;
+		proxy.last_delay().map(|delay| match delay {
+			0 => Delay::Failed,
+			delay => Delay::Measured(delay),
+		})
+	});
+
+	let (label, color): (String, fn(&cosmic::Theme) -> Color) = match delay {
+		None => (fl!("delay-untested"), |t| t.cosmic().on_bg_component_color().into()),
+		Some(Delay::Testing) => (fl!("delay-testing"), |t| t.cosmic().on_bg_component_color().into()),
+		Some(Delay::Failed) => (fl!("delay-failed"), |t| t.cosmic().destructive_color().into()),
+		Some(Delay::Measured(ms)) => (fl!("delay-ms", ms = ms.to_string()), delay_color(ms)),
+	};
+
+	let pill = widget::container(widget::text::caption(label))
+		.padding([space_xxs, space_xs])
+		.class(theme::Container::custom(move |t| widget::container::Style {
+			text_color: Some(color(t)),
+			border: Border {
+				color: color(t),
+				width: 1.0,
+				radius: 12.0.into(),
+			},
+			..Default::default()
+		}));
+
+	let mut badge = widget::mouse_area(pill);
+	if app.session.delays.get(name) != Some(&Delay::Testing) {
+		badge = badge.on_press(Message::TestDelay(name.to_string()));
+	}
+	badge.into()
+}
+
+fn delay_color(delay_ms: u64) -> fn(&cosmic::Theme) -> Color {
+	if delay_ms < FAST_DELAY_MS {
+		|t| t.cosmic().success_color().into()
+	} else if delay_ms < SLOW_DELAY_MS {
+		|t| t.cosmic().warning_color().into()
+	} else {
+		|t| t.cosmic().destructive_color().into()
+	}
+}
@@ -0,0 +1,290 @@
+// SPDX-License-Identifier: AGPL3.0
+
+use std::collections::HashMap;
+
+use cosmic::{
+	Element,
+	cosmic_theme,
+	iced::{Alignment, Background, Border, Length},
+	theme, widget,
+};
+
+use crate::{
+	api::Rule,
+	app::{AppModel, Message},
+	fl,
+	pages::home::api_error_row,
+};
+
+/// Profiles can carry tens of thousands of rules; only this many are drawn.
+const RULES_SHOWN: usize = 500;
+
+/// Rules passing the search and policy filter of the rules page.
+#[derive(Debug, Clone, Default)]
+pub struct RuleMatches {
+	/// Positions in the profile, in match order
+	pub indices: Vec<usize>,
+	/// Matching rules per type, most common first
+	pub type_counts: Vec<(String, usize)>,
+}
+
+/// Filters `rules` by a case-insensitive `search` and an exact `policy`.
+pub fn filter(rules: &[Rule], search: &str, policy: Option<&str>) -> RuleMatches {
+	// Keep the position in the profile, it is what decides which rule wins
+	let needle = search.trim().to_lowercase();
+	let indices: Vec<usize> = rules
+		.iter()
+		.enumerate()
+		.filter(|(_, rule)| policy.is_none_or(|policy| rule.proxy == policy))
+		.filter(|(_, rule)| {
+			needle.is_empty()
+				|| rule.payload.to_lowercase().contains(&needle)
+				|| rule.rule_type.to_lowercase().contains(&needle)
+				|| rule.proxy.to_lowercase().contains(&needle)
+		})
+		.map(|(index, _)| index)
+		.collect();
+
+	let mut counts: HashMap<&str, usize> = HashMap::new();
+	for index in &indices {
+		*counts.entry(rules[*index].rule_type.as_str()).or_default() += 1;
+	}
+	let mut type_counts: Vec<(String, usize)> = counts
+		.into_iter()
+		.map(|(rule_type, count)| (rule_type.to_string(), count))
+		.collect();
+	type_counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+	RuleMatches { indices, type_counts }
+}
+
+pub fn view_rules(app: &AppModel, _space_s: u16) -> Element<'_, Message> {
+	let cosmic_theme::Spacing {
+		space_xxs,
+		space_xs,
+		space_s,
+		space_m,
+		space_l,
+		..
+	} = theme::active().cosmic().spacing;
+
+	let header = widget::row::with_capacity(3)
+		.push(widget::text::title2(fl!("rules")))
+		.push(widget::space::horizontal().width(Length::Fill))
+		.push(
+			widget::button::standard(fl!("refresh"))
+				.on_press_maybe(app.session.api.as_ref().map(|_| Message::RefreshRules)),
+		)
+		.align_y(Alignment::Center)
+		.spacing(space_s)
+		.width(Length::Fill);
+
+	let mut layout = widget::column::with_capacity(3)
+		.push(header)
+		.spacing(space_m)
+		.width(Length::Fill);
+
+	if let Some(err) = app.controller_error() {
+		layout = layout.push(
+			widget::container(api_error_row(err, space_xs))
+				.padding(space_l)
+				.width(Length::Fill)
+				.class(theme::Container::Card),
+		);
+	}
+
+	let Some(rules) = app.session.rules.as_ref().filter(|_| app.session.api.is_some()) else {
+		return layout.push(empty_rules(app, space_s, space_m, space_l)).into();
+	};
+
+	let matching = &app.session.rule_matches;
+
+	let search = widget::text_input(fl!("search-rules"), &app.rule_search)
+		.on_input(Message::RuleSearchChanged)
+		.padding([space_xs, space_s])
+		.width(Length::Fill);
+
+	let policies = app.session.rule_policies.clone();
+	let selected = app
+		.rule_policy
+		.as_ref()
+		.and_then(|policy| app.session.rule_policies.iter().position(|p| p == policy));
+	let mut filters = widget::row::with_capacity(3)
+		.push(search)
+		.push(widget::dropdown(&app.session.rule_policies, selected, move |index| {
+			Message::RulePolicySelected(policies.get(index).cloned())
+		}))
+		.spacing(space_s)
+		.align_y(Alignment::Center)
+		.width(Length::Fill);
+	if app.rule_policy.is_some() {
+		filters = filters.push(widget::button::text(fl!("all-policies")).on_press(Message::RulePolicySelected(None)));
+	}
+
+	let count = matching.indices.len();
+	let summary = widget::text::caption(if count > RULES_SHOWN {
+		fl!(
+			"rules-shown-limited",
+			shown = RULES_SHOWN.to_string(),
+			matching = count.to_string(),
+			total = rules.len().to_string()
+		)
+	} else {
+		fl!("rules-shown", matching = count.to_string(), total = rules.len().to_string())
+	});
+
+	let mut list = widget::column::with_capacity(count.min(RULES_SHOWN))
+		.spacing(space_xxs)
+		.width(Length::Fill);
+	for index in matching.indices.iter().take(RULES_SHOWN) {
+		if let Some(rule) = rules.get(*index) {
+			list = list.push(rule_row(*index, rule, space_xs));
+		}
+	}
+
+	layout
+		.push(
+			widget::container(
+				widget::column::with_capacity(5)
+					.push(filters)
+					.push(type_counts(&matching.type_counts, space_xxs, space_xs))
+					.push(summary)
+					.push(widget::divider::horizontal::default())
+					.push(list)
+					.spacing(space_s)
+					.width(Length::Fill),
+			)
+			.padding(space_l)
+			.width(Length::Fill)
+			.class(theme::Container::Card),
+		)
+		.into()
+}
+
+fn empty_rules(app: &AppModel, space_s: u16, space_m: u16, space_l: u16) -> Element<'static, Message> {
+	let icon = widget::container(widget::icon::from_name("view-list-symbolic").size(48))
+		.padding(space_m)
+		.class(theme::Container::custom(|t| {
+			let cosmic = t.cosmic();
+			widget::container::Style {
+				background: Some(Background::Color(cosmic.bg_component_color().into())),
+				icon_color: Some(cosmic.on_bg_component_color().into()),
+				border: Border { radius: 64.0.into(), ..Default::default() },
+				..Default::default()
+			}
+		}));
+
+	let description = if app.session.api.is_none() {
+		fl!("rules-core-stopped")
+	} else {
+		fl!("rules-loading")
+	};
+
+	let column = widget::column::with_capacity(2)
+		.align_x(Alignment::Center)
+		.spacing(space_s)
+		.width(Length::Fill)
+		.push(icon)
+		.push(widget::text::body(description));
+
+	widget::container(column)
+		.padding(space_l + space_m)
+		.width(Length::Fill)
+		.class(theme::Container::Card)
+		.into()
+}
+
+/// One chip per rule type with how many of the matching rules use it.
+fn type_counts(counts: &[(String, usize)], space_xxs: u16, space_xs: u16) -> Element<'static, Message> {
+	let chips: Vec<Element<'static, Message>> = counts
+		.iter()
+		.map(|(rule_type, count)| {
+			widget::container(widget::text::caption(format!("{} {}", rule_type, count)))
+				.padding([space_xxs, space_xs])
+				.class(theme::Container::custom(|t| {
+					let cosmic = t.cosmic();
+					widget::container::Style {
+						background: Some(Background::Color(cosmic.bg_component_color().into())),
+						text_color: Some(cosmic.on_bg_component_color().into()),
+						border: Border { radius: 12.0.into(), ..Default::default() },
+						..Default::default()
+					}
+				}))
+				.into()
+		})
+		.collect();
+
+	widget::flex_row(chips)
+		.row_spacing(space_xxs)
+		.column_spacing(space_xs)
+		.into()
+}
+
+fn rule_row(index: usize, rule: &Rule, space_xs: u16) -> Element<'static, Message> {
+	widget::row::with_capacity(4)
+		.push(widget::text::caption((index + 1).to_string()).width(Length::Fixed(48.0)))
+		.push(widget::text::body(rule.rule_type.clone()).width(Length::FillPortion(2)))
+		.push(widget::text::body(rule.payload.clone()).width(Length::FillPortion(4)))
+		.push(widget::text::body(rule.proxy.clone()).width(Length::FillPortion(2)))
+		.spacing(space_xs)
+		.align_y(Alignment::Center)
+		.width(Length::Fill)
+		.into()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn rules() -> Vec<Rule> {
+		[
+			("DOMAIN-SUFFIX", "google.com", "Proxy"),
+			("DOMAIN-KEYWORD", "ads", "REJECT"),
+			("DOMAIN-SUFFIX", "example.org", "DIRECT"),
+			("GEOIP", "CN", "DIRECT"),
+			("MATCH", "", "Proxy"),
+		]
+		.into_iter()
+		.map(|(rule_type, payload, proxy)| Rule {
+			rule_type: rule_type.to_string(),
+			payload: payload.to_string(),
+			proxy: proxy.to_string(),
+		})
+		.collect()
+	}
+
+	#[test]
+	fn empty_search_keeps_every_rule() {
+		let rules = rules();
+		let matches = filter(&rules, "  ", None);
+		assert_eq!(matches.indices, [0, 1, 2, 3, 4]);
+		assert_eq!(
+			matches.type_counts,
+			[
+				("DOMAIN-SUFFIX".to_string(), 2),
+				("DOMAIN-KEYWORD".to_string(), 1),
+				("GEOIP".to_string(), 1),
+				("MATCH".to_string(), 1),
+			]
+		);
+	}
+
+	#[test]
+	fn search_matches_payload_type_and_proxy() {
+		let rules = rules();
+		assert_eq!(filter(&rules, "Google", None).indices, [0]);
+		assert_eq!(filter(&rules, "suffix", None).indices, [0, 2]);
+		assert_eq!(filter(&rules, "direct", None).indices, [2, 3]);
+		assert!(filter(&rules, "nothing", None).indices.is_empty());
+	}
+
+	#[test]
+	fn policy_must_match_exactly() {
+		let rules = rules();
+		let matches = filter(&rules, "", Some("Proxy"));
+		assert_eq!(matches.indices, [0, 4]);
+		assert_eq!(matches.type_counts.len(), 2);
+		assert!(filter(&rules, "", Some("proxy")).indices.is_empty());
+		assert_eq!(filter(&rules, "org", Some("DIRECT")).indices, [2]);
+	}
+}
@@ -8,7 +8,7 @@ use cosmic::{
 };
 
 use crate::{
-	app::{AppModel, Message, SettingField},
+	app::{AppModel, CoreUpdateState, Message, SettingField},
 	fl,
 };
 
@@ -38,6 +38,7 @@ pub fn view_settings(app: &AppModel, _space_s: u16) -> Element<'_, Message> {
 			space_xs,
 			space_s,
 		))
+		.add(core_info_item(app))
 		.add(setting_item(
 			app,
 			SettingField::ConfigDir,
@@ -48,8 +49,36 @@ pub fn view_settings(app: &AppModel, _space_s: u16) -> Element<'_, Message> {
 				.unwrap_or_else(|| fl!("default")),
 			space_xs,
 			space_s,
+		))
+		.add(setting_item(
+			app,
+			SettingField::MaxRestarts,
+			fl!("max-restarts"),
+			app.config.max_restarts.to_string(),
+			space_xs,
+			space_s,
+		))
+		.add(setting_item(
+			app,
+			SettingField::StopTimeout,
+			fl!("stop-timeout"),
+			fl!("seconds", secs = app.config.stop_timeout_secs.to_string()),
+			space_xs,
+			space_s,
 		));
 
+	let core_section = widget::settings::section()
+		.title(fl!("core-management"))
+		.add(setting_item(
+			app,
+			SettingField::ManifestUrl,
+			fl!("manifest-url"),
+			app.config.core_manifest_url.clone().unwrap_or_else(|| fl!("none")),
+			space_xs,
+			space_s,
+		))
+		.add(core_update_item(app, space_xs));
+
 	let api_section = widget::settings::section()
 		.title(fl!("api-settings"))
 		.add(setting_item(
@@ -73,16 +102,129 @@ pub fn view_settings(app: &AppModel, _space_s: u16) -> Element<'_, Message> {
 			space_s,
 		));
 
+	let delay_section = widget::settings::section()
+		.title(fl!("latency-test"))
+		.add(setting_item(
+			app,
+			SettingField::DelayTestUrl,
+			fl!("delay-test-url"),
+			app.config.delay_test_url.clone(),
+			space_xs,
+			space_s,
+		))
+		.add(setting_item(
+			app,
+			SettingField::DelayTimeout,
+			fl!("delay-timeout"),
+			fl!("milliseconds", ms = app.config.delay_timeout_ms.to_string()),
+			space_xs,
+			space_s,
+		));
+
+	let usage_section = widget::settings::section().title(fl!("traffic-usage")).add(setting_item(
+		app,
+		SettingField::MonthlyQuota,
+		fl!("monthly-quota"),
+		match app.config.monthly_quota_gib.filter(|gib| *gib > 0) {
+			Some(gib) => fl!("gibibytes", gib = gib.to_string()),
+			None => fl!("none"),
+		},
+		space_xs,
+		space_s,
+	));
+
 	widget::settings::view_column(vec![
 		header.into(),
 		app_section.into(),
+		core_section.into(),
 		api_section.into(),
+		delay_section.into(),
+		usage_section.into(),
 	])
 	.spacing(space_m)
 	.width(Length::Fill)
 	.into()
 }
 
+fn core_info_item(app: &AppModel) -> Element<'_, Message> {
+	let (description, icon) = match &app.core_info {
+		None => (fl!("core-detecting"), "content-loading-symbolic"),
+		Some(Err(err)) => (fl!("core-not-recognized", reason = err.clone()), "dialog-error-symbolic"),
+		Some(Ok(info)) => {
+			let version = info
+				.version
+				.as_ref()
+				.map(ToString::to_string)
+				.unwrap_or_else(|| fl!("unknown"));
+			match info.minimum_version() {
+				Some(minimum) if !info.is_supported() => (
+					fl!(
+						"core-too-old",
+						flavour = info.flavour.to_string(),
+						version = version,
+						minimum = minimum.to_string()
+					),
+					"dialog-warning-symbolic",
+				),
+				_ => (
+					fl!("core-detected", flavour = info.flavour.to_string(), version = version),
+					"emblem-default-symbolic",
+				),
+			}
+		}
+	};
+
+	widget::settings::item::builder(fl!("clash-core"))
+		.description(description)
+		.icon(widget::icon::from_name(icon).size(16))
+		.control(widget::text::caption(
+			app.core_info
+				.as_ref()
+				.and_then(|info| info.as_ref().ok())
+				.map(|info| info.raw.clone())
+				.unwrap_or_default(),
+		))
+		.into()
+}
+
+fn core_update_item(app: &AppModel, space_xs: u16) -> Element<'_, Message> {
+	let description = match &app.core_update {
+		CoreUpdateState::Idle => fl!("core-update-idle"),
+		CoreUpdateState::Checking => fl!("core-update-checking"),
+		CoreUpdateState::UpToDate => fl!("core-update-latest"),
+		CoreUpdateState::Available(update) => fl!("core-update-available", version = update.version.to_string()),
+		CoreUpdateState::Installing(update) => fl!("core-update-installing", version = update.version.to_string()),
+		CoreUpdateState::Installed(installed) => {
+			fl!("core-update-installed", version = installed.version.to_string())
+		}
+		CoreUpdateState::Failed(err) => fl!("core-update-failed", reason = err.clone()),
+	};
+
+	let busy = matches!(app.core_update, CoreUpdateState::Checking | CoreUpdateState::Installing(_));
+	let mut actions = widget::row::with_capacity(3).spacing(space_xs);
+
+	if app.config.previous_clash_binary_path.is_some() {
+		actions = actions.push(
+			widget::button::standard(fl!("rollback")).on_press_maybe((!busy).then_some(Message::RollbackCore)),
+		);
+	}
+
+	actions = if let CoreUpdateState::Available(_) = &app.core_update {
+		actions.push(widget::button::suggested(fl!("install")).on_press(Message::InstallCoreUpdate))
+	} else {
+		let can_check = !busy && app.config.core_manifest_url.is_some();
+		actions.push(
+			widget::button::standard(fl!("check-for-updates"))
+				.on_press_maybe(can_check.then_some(Message::CheckCoreUpdate)),
+		)
+	};
+
+	widget::settings::item::builder(fl!("core-updates"))
+		.description(description)
+		.control(actions)
+		.into()
+}
+
 fn setting_item<'a>(
 	app: &'a AppModel,
 	field: SettingField,
@@ -99,6 +241,12 @@ fn setting_item<'a>(
 			SettingField::ConfigDir => fl!("config-dir-placeholder"),
 			SettingField::ApiPort => fl!("api-port-placeholder"),
 			SettingField::ApiSecret => fl!("api-secret-placeholder"),
+			SettingField::MaxRestarts => fl!("max-restarts-placeholder"),
+			SettingField::StopTimeout => fl!("stop-timeout-placeholder"),
+			SettingField::ManifestUrl => fl!("manifest-url-placeholder"),
+			SettingField::DelayTestUrl => fl!("delay-test-url-placeholder"),
+			SettingField::DelayTimeout => fl!("delay-timeout-placeholder"),
+			SettingField::MonthlyQuota => fl!("monthly-quota-placeholder"),
 		};
 
 		let mut input = widget::text_input(placeholder, &app.edit_value)
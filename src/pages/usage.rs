@@ -0,0 +1,195 @@
+// SPDX-License-Identifier: AGPL3.0
+
+use cosmic::{
+	Element, cosmic_theme,
+	iced::{Alignment, Background, Border, Length},
+	theme, widget,
+};
+
+use crate::{
+	app::{AppModel, Message},
+	fl,
+	pages::connections::format_bytes,
+	usage::{self, Usage},
+};
+
+/// Days listed in the daily breakdown.
+const DAYS_SHOWN: usize = 30;
+
+/// Share of the monthly quota after which the page warns.
+const QUOTA_WARNING_RATIO: f32 = 0.9;
+
+pub fn view_usage(app: &AppModel, _space_s: u16) -> Element<'_, Message> {
+	let cosmic_theme::Spacing {
+		space_xxs,
+		space_xs,
+		space_s,
+		space_m,
+		space_l,
+		..
+	} = theme::active().cosmic().spacing;
+
+	let header = widget::row::with_capacity(1)
+		.push(widget::text::title2(fl!("usage")))
+		.align_y(Alignment::Center)
+		.width(Length::Fill);
+
+	let mut layout = widget::column::with_capacity(4)
+		.push(header)
+		.spacing(space_m)
+		.width(Length::Fill);
+
+	let days = app.usage.recent_days(DAYS_SHOWN);
+	if days.is_empty() {
+		return layout.push(empty_usage(space_s, space_m, space_l)).into();
+	}
+
+	let today = usage::today();
+	let month = usage::month_of(&today);
+	layout = layout.push(month_card(app, &today, space_xxs, space_xs, space_s, space_l));
+
+	let profiles = app.usage.month_by_profile(month);
+	if !profiles.is_empty() {
+		let mut section = widget::settings::section().title(fl!("usage-by-profile"));
+		for (file, usage) in profiles {
+			// Show the display name of profiles that are still around
+			let label = app.profiles.get(&file).map_or(file, |profile| profile.name.clone());
+			section = section.add(usage_item(label, usage));
+		}
+		layout = layout.push(Element::from(section));
+	}
+
+	let mut section = widget::settings::section().title(fl!("usage-by-day"));
+	for (day, usage) in days {
+		section = section.add(usage_item(day, usage));
+	}
+	layout.push(Element::from(section)).into()
+}
+
+fn empty_usage(space_s: u16, space_m: u16, space_l: u16) -> Element<'static, Message> {
+	let icon = widget::container(widget::icon::from_name("utilities-system-monitor-symbolic").size(48))
+		.padding(space_m)
+		.class(theme::Container::custom(|t| {
+			let cosmic = t.cosmic();
+			widget::container::Style {
+				background: Some(Background::Color(cosmic.bg_component_color().into())),
+				icon_color: Some(cosmic.on_bg_component_color().into()),
+				border: Border {
+					radius: 64.0.into(),
+					..Default::default()
+				},
+				..Default::default()
+			}
+		}));
+
+	let column = widget::column::with_capacity(2)
+		.align_x(Alignment::Center)
+		.spacing(space_s)
+		.width(Length::Fill)
+		.push(icon)
+		.push(widget::text::body(fl!("no-usage")));
+
+	widget::container(column)
+		.padding(space_l + space_m)
+		.width(Length::Fill)
+		.class(theme::Container::Card)
+		.into()
+}
+
+/// This month's total against the quota, with today's share below it.
+fn month_card(
+	app: &AppModel,
+	today: &str,
+	space_xxs: u16,
+	space_xs: u16,
+	space_s: u16,
+	space_l: u16,
+) -> Element<'static, Message> {
+	let month_usage = app.usage.month(usage::month_of(today));
+	let today_usage = app.usage.day(today);
+
+	let mut column = widget::column::with_capacity(5)
+		.push(widget::text::heading(fl!("usage-this-month")))
+		.push(widget::text::title3(format_bytes(month_usage.total())))
+		.spacing(space_s)
+		.width(Length::Fill);
+
+	if let Some(quota) = app.config.monthly_quota_bytes() {
+		let ratio = month_usage.total() as f32 / quota as f32;
+		column = column
+			.push(cosmic::iced::widget::progress_bar(0.0..=1.0, ratio.min(1.0)))
+			.push(widget::text::caption(fl!(
+				"usage-of-quota",
+				used = format_bytes(month_usage.total()),
+				quota = format_bytes(quota),
+				percent = format!("{:.0}", ratio * 100.0)
+			)));
+		if ratio >= QUOTA_WARNING_RATIO {
+			column = column.push(quota_warning(ratio >= 1.0, space_xs));
+		}
+	}
+
+	column = column.push(
+		widget::column::with_capacity(2)
+			.push(breakdown_row(fl!("usage-today"), today_usage))
+			.push(breakdown_row(fl!("usage-month-split"), month_usage))
+			.spacing(space_xxs),
+	);
+
+	widget::container(column)
+		.padding(space_l)
+		.width(Length::Fill)
+		.class(theme::Container::Card)
+		.into()
+}
+
+fn quota_warning(exceeded: bool, space_xs: u16) -> Element<'static, Message> {
+	let text = if exceeded {
+		fl!("quota-exceeded")
+	} else {
+		fl!("quota-nearly-used")
+	};
+
+	let row = widget::row::with_capacity(2)
+		.push(widget::icon::from_name("dialog-warning-symbolic").size(16))
+		.push(widget::text::body(text))
+		.spacing(space_xs)
+		.align_y(Alignment::Center);
+
+	widget::container(row)
+		.class(theme::Container::custom(move |t| {
+			let cosmic = t.cosmic();
+			let color = if exceeded {
+				cosmic.destructive_color()
+			} else {
+				cosmic.warning_color()
+			};
+			widget::container::Style {
+				text_color: Some(color.into()),
+				icon_color: Some(color.into()),
+				..Default::default()
+			}
+		}))
+		.into()
+}
+
+fn breakdown_row(label: String, usage: Usage) -> Element<'static, Message> {
+	widget::row::with_capacity(3)
+		.push(widget::text::caption(label))
+		.push(widget::space::horizontal().width(Length::Fill))
+		.push(widget::text::body(usage_split(usage)))
+		.align_y(Alignment::Center)
+		.width(Length::Fill)
+		.into()
+}
+
+fn usage_item(label: String, usage: Usage) -> Element<'static, Message> {
+	widget::settings::item::builder(label)
+		.description(usage_split(usage))
+		.control(widget::text::body(format_bytes(usage.total())))
+		.into()
+}
+
+fn usage_split(usage: Usage) -> String {
+	fl!("usage-split", up = format_bytes(usage.up), down = format_bytes(usage.down))
+}
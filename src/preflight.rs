@@ -0,0 +1,162 @@
+// SPDX-License-Identifier: AGPL3.0
+
+use std::{
+	path::{Path, PathBuf},
+	process::Stdio,
+	time::Duration,
+};
+
+use tokio::process::Command;
+
+use crate::core_info::CoreFlavour;
+
+/// How long the core may take to check a config file.
+const VALIDATION_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Why the core rejected a config file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigIssue {
+	pub message: String,
+	/// 1-based line in the YAML file, when the core reported one.
+	pub line: Option<u32>,
+	/// The file `line` refers to, when it is not the profile itself.
+	pub file: Option<PathBuf>,
+}
+
+impl std::fmt::Display for ConfigIssue {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match (self.line, &self.file) {
+			(Some(line), Some(file)) => write!(f, "{} line {}: {}", file.display(), line, self.message),
+			(Some(line), None) => write!(f, "line {}: {}", line, self.message),
+			(None, _) => write!(f, "{}", self.message),
+		}
+	}
+}
+
+impl std::error::Error for ConfigIssue {}
+
+/// Runs `binary` in config-test mode (`-t`) against `config_path`.
+pub async fn validate_config(
+	binary: &Path,
+	flavour: CoreFlavour,
+	work_dir: &Path,
+	config_path: &Path,
+) -> Result<(), ConfigIssue> {
+	if !config_path.is_file() {
+		return Err(ConfigIssue {
+			message: format!("{} does not exist", config_path.display()),
+			line: None,
+			file: None,
+		});
+	}
+
+	let mut cmd = Command::new(binary);
+	cmd.args(flavour.config_args(work_dir, config_path))
+		.arg("-t")
+		.current_dir(work_dir)
+		.stdin(Stdio::null())
+		.kill_on_drop(true);
+
+	let output = match tokio::time::timeout(VALIDATION_TIMEOUT, cmd.output()).await {
+		Ok(Ok(output)) => output,
+		Ok(Err(err)) => {
+			return Err(ConfigIssue {
+				message: format!("failed to run {}: {}", binary.display(), err),
+				line: None,
+				file: None,
+			});
+		}
+		Err(_) => {
+			return Err(ConfigIssue {
+				message: format!("config test did not finish within {:?}", VALIDATION_TIMEOUT),
+				line: None,
+				file: None,
+			});
+		}
+	};
+
+	if output.status.success() {
+		tracing::debug!("Config {:?} passed validation", config_path);
+		return Ok(());
+	}
+
+	let stdout = String::from_utf8_lossy(&output.stdout);
+	let stderr = String::from_utf8_lossy(&output.stderr);
+	let issue = parse_issue(&format!("{}\n{}", stdout, stderr));
+	tracing::warn!("Config {:?} failed validation: {}", config_path, issue);
+	Err(issue)
+}
+
+/// Picks the most relevant line out of the core's test output.
+fn parse_issue(output: &str) -> ConfigIssue {
+	let lines: Vec<&str> = output.lines().map(str::trim).filter(|l| !l.is_empty()).collect();
+	let message = lines
+		.iter()
+		.rev()
+		.find(|l| l.to_ascii_lowercase().contains("error"))
+		.or_else(|| lines.last())
+		.map(|l| l.to_string())
+		.unwrap_or_else(|| "config test failed".to_string());
+	let line = find_line_number(&message).or_else(|| lines.iter().find_map(|l| find_line_number(l)));
+	ConfigIssue {
+		message,
+		line,
+		file: None,
+	}
+}
+
+/// Extracts `N` from "line N" as printed by the YAML parsers of clash-rs and
+/// mihomo.
+fn find_line_number(text: &str) -> Option<u32> {
+	let lower = text.to_ascii_lowercase();
+	lower.match_indices("line ").find_map(|(idx, needle)| {
+		let digits: String = lower[idx + needle.len()..]
+			.chars()
+			.take_while(char::is_ascii_digit)
+			.collect();
+		digits.parse().ok()
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn parse_issue_prefers_the_last_error_line() {
+		let output = "loading config\nerror: first\nsome context\nERROR invalid proxy at line 12 column 3\n";
+		assert_eq!(
+			parse_issue(output),
+			ConfigIssue {
+				message: "ERROR invalid proxy at line 12 column 3".to_string(),
+				line: Some(12),
+				file: None,
+			}
+		);
+	}
+
+	#[test]
+	fn parse_issue_falls_back_to_the_last_line() {
+		let issue = parse_issue("  \nsomething went wrong\n\n");
+		assert_eq!(issue.message, "something went wrong");
+		assert_eq!(issue.line, None);
+	}
+
+	#[test]
+	fn parse_issue_takes_the_line_from_other_output() {
+		let issue = parse_issue("yaml: line 4: did not find expected key\nerror: parse failed");
+		assert_eq!(issue.message, "error: parse failed");
+		assert_eq!(issue.line, Some(4));
+	}
+
+	#[test]
+	fn parse_issue_handles_empty_output() {
+		assert_eq!(parse_issue("").message, "config test failed");
+	}
+
+	#[test]
+	fn find_line_number_needs_digits() {
+		assert_eq!(find_line_number("Line 42: bad"), Some(42));
+		assert_eq!(find_line_number("no line here"), None);
+	}
+}
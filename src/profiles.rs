@@ -0,0 +1,698 @@
+// SPDX-License-Identifier: AGPL3.0
+
+//! Index of the profiles in the config dir, with what we know about each:
+//! where it comes from, when it changed and when it was last used. Remote
+//! profiles are downloaded from a subscription URL.
+
+use std::{
+	path::{Path, PathBuf},
+	time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use reqwest::{
+	StatusCode,
+	header::{ETAG, HeaderMap, HeaderName, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED, USER_AGENT},
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::api::SubscriptionInfo;
+
+/// Subscription services pick the config format from the User-Agent, so the
+/// default one mentions clash.
+pub const DEFAULT_USER_AGENT: &str = concat!("clash cosmic-clash/", env!("CARGO_PKG_VERSION"));
+
+/// Refresh interval offered for new remote profiles, in hours.
+pub const DEFAULT_REFRESH_HOURS: u32 = 24;
+
+const FETCH_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Quota and expiry header sent by most subscription services.
+const SUBSCRIPTION_USERINFO: HeaderName = HeaderName::from_static("subscription-userinfo");
+
+/// Wait after a failed background refresh before trying again, in seconds.
+const RETRY_AFTER_SECS: i64 = 15 * 60;
+
+/// File stem of the config the core loads when no profile is selected.
+pub const DEFAULT_FILE: &str = "config";
+
+/// File stem of the generated config the core is launched with, see
+/// [`crate::overrides`].
+pub const RUNTIME_FILE: &str = "cosmic-clash-runtime";
+
+/// Files in the config dir that belong to the core rather than to a profile.
+const RESERVED_FILES: &[&str] = &[DEFAULT_FILE, RUNTIME_FILE];
+
+/// One profile of the index, backed by `<config dir>/<file>.yaml`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Profile {
+	/// File stem in the config dir. It never changes, so it is what
+	/// `Config::active_profile` and the usage store refer to.
+	pub file: String,
+	/// Name shown in the GUI; renaming only touches this.
+	pub name: String,
+	pub source: ProfileSource,
+	/// Unix time the profile was added.
+	pub created_at: i64,
+	/// Unix time the file's content last changed.
+	#[serde(default)]
+	pub updated_at: Option<i64>,
+	/// Hex SHA-256 of the file; `None` while there is no file.
+	#[serde(default)]
+	pub hash: Option<String>,
+	#[serde(default)]
+	pub notes: String,
+	/// Unix time the profile was last activated.
+	#[serde(default)]
+	pub last_used_at: Option<i64>,
+}
+
+/// Where a profile's YAML comes from.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum ProfileSource {
+	/// A file put into the config dir by hand, or a duplicate.
+	Local,
+	/// Downloaded from a subscription URL.
+	Remote(RemoteSource),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RemoteSource {
+	pub url: String,
+	/// Sent instead of [`DEFAULT_USER_AGENT`] when set.
+	#[serde(default)]
+	pub user_agent: Option<String>,
+	/// Hours between background refreshes; 0 only refreshes on request.
+	#[serde(default)]
+	pub refresh_hours: u32,
+	/// Validators of the last download, sent back to skip unchanged bodies.
+	#[serde(default)]
+	pub etag: Option<String>,
+	#[serde(default)]
+	pub last_modified: Option<String>,
+	/// Unix time of the last successful check, changed or not.
+	#[serde(default)]
+	pub checked_at: Option<i64>,
+	/// Unix time of the last failed fetch, cleared by a successful one.
+	#[serde(default)]
+	pub failed_at: Option<i64>,
+	/// Quota reported by the last fetch's `subscription-userinfo` header.
+	#[serde(default)]
+	pub userinfo: Option<Userinfo>,
+}
+
+/// A subscription quota as stored in the index. It is kept apart from
+/// [`SubscriptionInfo`], whose field names follow the core's provider API.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct Userinfo {
+	pub upload: u64,
+	pub download: u64,
+	pub total: u64,
+	/// Unix time the subscription runs out; 0 if it does not.
+	pub expire: i64,
+}
+
+impl From<SubscriptionInfo> for Userinfo {
+	fn from(info: SubscriptionInfo) -> Self {
+		Self {
+			upload: info.upload,
+			download: info.download,
+			total: info.total,
+			expire: info.expire,
+		}
+	}
+}
+
+impl From<Userinfo> for SubscriptionInfo {
+	fn from(userinfo: Userinfo) -> Self {
+		Self {
+			upload: userinfo.upload,
+			download: userinfo.download,
+			total: userinfo.total,
+			expire: userinfo.expire,
+		}
+	}
+}
+
+impl Profile {
+	pub fn new(file: String, name: String, source: ProfileSource, now: i64) -> Self {
+		Self {
+			file,
+			name,
+			source,
+			created_at: now,
+			updated_at: None,
+			hash: None,
+			notes: String::new(),
+			last_used_at: None,
+		}
+	}
+
+	pub fn path(&self, config_dir: &Path) -> PathBuf {
+		resolve(config_dir, Some(&self.file))
+	}
+
+	pub fn remote(&self) -> Option<&RemoteSource> {
+		match &self.source {
+			ProfileSource::Remote(remote) => Some(remote),
+			ProfileSource::Local => None,
+		}
+	}
+
+	pub fn remote_mut(&mut self) -> Option<&mut RemoteSource> {
+		match &mut self.source {
+			ProfileSource::Remote(remote) => Some(remote),
+			ProfileSource::Local => None,
+		}
+	}
+
+	/// Whether the YAML file exists, as of the last scan.
+	pub fn is_downloaded(&self) -> bool {
+		self.hash.is_some()
+	}
+}
+
+impl RemoteSource {
+	pub fn new(url: String, user_agent: Option<String>, refresh_hours: u32) -> Self {
+		Self {
+			url,
+			user_agent,
+			refresh_hours,
+			etag: None,
+			last_modified: None,
+			checked_at: None,
+			failed_at: None,
+			userinfo: None,
+		}
+	}
+
+	/// The quota of the last fetch, in the form the GUI shows.
+	pub fn quota(&self) -> Option<SubscriptionInfo> {
+		self.userinfo.map(SubscriptionInfo::from)
+	}
+
+	/// Records a failed fetch, holding off background retries for a while.
+	pub fn fail(&mut self, now: i64) {
+		self.failed_at = Some(now);
+	}
+
+	/// Whether the background refresh should fetch this profile at `now`.
+	pub fn is_due(&self, now: i64) -> bool {
+		if self.refresh_hours == 0 {
+			return false;
+		}
+		if self.failed_at.is_some_and(|failed_at| now - failed_at < RETRY_AFTER_SECS) {
+			return false;
+		}
+		match self.checked_at {
+			Some(checked_at) => now - checked_at >= i64::from(self.refresh_hours) * 3600,
+			None => true,
+		}
+	}
+
+	/// Records a finished fetch.
+	pub fn apply(&mut self, fetched: &Fetched, now: i64) {
+		self.checked_at = Some(now);
+		self.failed_at = None;
+		// A 304 need not repeat the header, so keep what we have
+		if let Some(info) = fetched.userinfo {
+			self.userinfo = Some(info.into());
+		}
+		if let FetchOutcome::Updated { etag, last_modified } = &fetched.outcome {
+			self.etag = etag.clone();
+			self.last_modified = last_modified.clone();
+		}
+	}
+}
+
+/// Result of one successful fetch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fetched {
+	pub outcome: FetchOutcome,
+	pub userinfo: Option<SubscriptionInfo>,
+}
+
+/// What a fetch did to the file on disk.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FetchOutcome {
+	/// The server answered 304; the cached file is current.
+	NotModified,
+	/// A new body was written, with the validators that came with it.
+	Updated {
+		etag: Option<String>,
+		last_modified: Option<String>,
+	},
+}
+
+/// A YAML file found in the config dir.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScannedFile {
+	pub file: String,
+	pub hash: String,
+}
+
+/// All known profiles, persisted next to the app config.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProfileIndex {
+	pub profiles: Vec<Profile>,
+}
+
+impl ProfileIndex {
+	fn path() -> PathBuf {
+		dirs::config_dir()
+			.unwrap_or_else(|| PathBuf::from("."))
+			.join("cosmic-clash")
+			.join("profiles.json")
+	}
+
+	/// Reads the index. One that does not parse is moved aside rather than
+	/// left for the next save to overwrite.
+	pub fn load() -> eyre::Result<Self> {
+		Self::load_from(&Self::path())
+	}
+
+	fn load_from(path: &Path) -> eyre::Result<Self> {
+		if !path.exists() {
+			return Ok(Self::default());
+		}
+		let json = std::fs::read_to_string(path)?;
+		match serde_json::from_str(&json) {
+			Ok(index) => Ok(index),
+			Err(err) => {
+				let backup = path.with_extension("json.corrupt");
+				std::fs::rename(path, &backup)?;
+				Err(eyre::eyre!("{} (moved to {})", err, backup.display()))
+			}
+		}
+	}
+
+	pub fn save(&self) -> eyre::Result<()> {
+		let path = Self::path();
+		if let Some(parent) = path.parent() {
+			std::fs::create_dir_all(parent)?;
+		}
+		let json = serde_json::to_string_pretty(self)?;
+		let tmp = path.with_extension("json.tmp");
+		std::fs::write(&tmp, json)?;
+		std::fs::rename(tmp, path)?;
+		Ok(())
+	}
+
+	pub fn get(&self, file: &str) -> Option<&Profile> {
+		self.profiles.iter().find(|profile| profile.file == file)
+	}
+
+	pub fn get_mut(&mut self, file: &str) -> Option<&mut Profile> {
+		self.profiles.iter_mut().find(|profile| profile.file == file)
+	}
+
+	/// Adds `profile`, replacing one with the same file.
+	pub fn insert(&mut self, profile: Profile) {
+		match self.get_mut(&profile.file) {
+			Some(existing) => *existing = profile,
+			None => self.profiles.push(profile),
+		}
+	}
+
+	pub fn remove(&mut self, file: &str) -> Option<Profile> {
+		let index = self.profiles.iter().position(|profile| profile.file == file)?;
+		Some(self.profiles.remove(index))
+	}
+
+	/// Remote profiles whose refresh interval has passed.
+	pub fn due(&self, now: i64) -> impl Iterator<Item = &Profile> {
+		self.profiles
+			.iter()
+			.filter(move |profile| profile.remote().is_some_and(|remote| remote.is_due(now)))
+	}
+
+	/// Whether any profile refreshes in the background.
+	pub fn has_scheduled_refresh(&self) -> bool {
+		self.profiles
+			.iter()
+			.any(|profile| profile.remote().is_some_and(|remote| remote.refresh_hours > 0))
+	}
+
+	/// Brings the index in line with a scan of the config dir: new files
+	/// become local profiles, changed files get a new hash and update time,
+	/// and local profiles whose file is gone are dropped. Remote profiles are
+	/// kept without a file, since they can be downloaded again.
+	pub fn sync(&mut self, scanned: &[ScannedFile], now: i64) {
+		for found in scanned {
+			match self.get_mut(&found.file) {
+				Some(profile) => {
+					if profile.hash.as_deref() != Some(found.hash.as_str()) {
+						profile.hash = Some(found.hash.clone());
+						profile.updated_at = Some(now);
+					}
+				}
+				None => {
+					let mut profile = Profile::new(found.file.clone(), found.file.clone(), ProfileSource::Local, now);
+					profile.hash = Some(found.hash.clone());
+					profile.updated_at = Some(now);
+					self.profiles.push(profile);
+				}
+			}
+		}
+
+		let on_disk = |profile: &Profile| scanned.iter().any(|found| found.file == profile.file);
+		self.profiles.retain(|profile| profile.remote().is_some() || on_disk(profile));
+		for profile in &mut self.profiles {
+			if !scanned.iter().any(|found| found.file == profile.file) {
+				profile.hash = None;
+			}
+		}
+	}
+
+	/// A file stem based on `base` that is neither indexed nor on disk.
+	pub fn unique_file(&self, config_dir: &Path, base: &str) -> String {
+		let taken =
+			|file: &str| self.get(file).is_some() || is_reserved(file) || config_dir.join(format!("{}.yaml", file)).exists();
+		if !taken(base) {
+			return base.to_string();
+		}
+		(2..)
+			.map(|n| format!("{}-{}", base, n))
+			.find(|file| !taken(file))
+			.unwrap_or_default()
+	}
+}
+
+pub fn is_reserved(file: &str) -> bool {
+	RESERVED_FILES.contains(&file)
+}
+
+/// Lists the profile YAML files in `config_dir` with their content hashes.
+/// A missing dir has no profiles; a dir that cannot be read is an error, so
+/// callers do not mistake it for an empty one.
+pub fn scan(config_dir: &Path) -> std::io::Result<Vec<ScannedFile>> {
+	let entries = match std::fs::read_dir(config_dir) {
+		Ok(entries) => entries,
+		Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+		Err(err) => return Err(err),
+	};
+	let mut scanned = Vec::new();
+	for entry in entries.flatten() {
+		let path = entry.path();
+		if !path.extension().is_some_and(|ext| ext == "yaml" || ext == "yml") {
+			continue;
+		}
+		let Some(file) = path.file_stem().and_then(|stem| stem.to_str()) else {
+			continue;
+		};
+		if is_reserved(file) {
+			continue;
+		}
+		match std::fs::read(&path) {
+			Ok(content) => scanned.push(ScannedFile {
+				file: file.to_string(),
+				hash: format!("{:x}", Sha256::digest(&content)),
+			}),
+			Err(err) => tracing::warn!("Skipping unreadable profile {:?}: {}", path, err),
+		}
+	}
+	scanned.sort_by(|a, b| a.file.cmp(&b.file));
+	Ok(scanned)
+}
+
+/// Absolute path of the file the core runs for `profile`, or of its default
+/// `config.yaml` when none is selected. Starting, reloading and selecting all
+/// go through here so they cannot disagree on which file is live.
+///
+/// Profiles are `<file>.yaml`, falling back to `<file>.yml` when only that
+/// exists, since [`scan`] picks up both.
+pub fn resolve(config_dir: &Path, profile: Option<&str>) -> PathBuf {
+	let file = profile.unwrap_or(DEFAULT_FILE);
+	let mut path = config_dir.join(format!("{}.yaml", file));
+	if !path.exists() {
+		let yml = config_dir.join(format!("{}.yml", file));
+		if yml.exists() {
+			path = yml;
+		}
+	}
+	// The core runs from its own work dir, where a relative path means something else
+	std::path::absolute(&path).unwrap_or(path)
+}
+
+/// Copies the file of `profile` to `<config dir>/<file>.yaml`.
+pub async fn duplicate(config_dir: &Path, profile: &Profile, file: &str) -> eyre::Result<()> {
+	tokio::fs::copy(profile.path(config_dir), config_dir.join(format!("{}.yaml", file))).await?;
+	Ok(())
+}
+
+/// Deletes the file of `profile`, and nothing else.
+pub async fn delete(config_dir: &Path, profile: &Profile) -> eyre::Result<()> {
+	match tokio::fs::remove_file(profile.path(config_dir)).await {
+		Ok(()) => Ok(()),
+		// A remote profile may never have been downloaded
+		Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(()),
+		Err(err) => Err(err.into()),
+	}
+}
+
+/// Rejects names that would escape the config dir, hide the file or clash
+/// with a file of the core.
+pub fn validate_name(name: &str) -> eyre::Result<()> {
+	if name.trim().is_empty() {
+		return Err(eyre::eyre!("the name is empty"));
+	}
+	if is_reserved(name) {
+		return Err(eyre::eyre!("{:?} is reserved for the core", name));
+	}
+	if name.starts_with('.') || name.contains(['/', '\\']) {
+		return Err(eyre::eyre!("{:?} is not a valid file name", name));
+	}
+	Ok(())
+}
+
+/// Downloads the remote `profile` into `config_dir`.
+///
+/// The cached validators are only sent while the file still exists, so a
+/// deleted file is always fetched again. The body is written next to the
+/// target and renamed over it, so the core never reads a partial profile.
+pub async fn fetch(client: &reqwest::Client, profile: &Profile, config_dir: &Path) -> eyre::Result<Fetched> {
+	let Some(remote) = profile.remote() else {
+		return Err(eyre::eyre!("{:?} is not a remote profile", profile.name));
+	};
+	let path = profile.path(config_dir);
+	let user_agent = remote.user_agent.as_deref().unwrap_or(DEFAULT_USER_AGENT);
+
+	let mut request = client.get(&remote.url).header(USER_AGENT, user_agent).timeout(FETCH_TIMEOUT);
+	if path.exists() {
+		if let Some(etag) = &remote.etag {
+			request = request.header(IF_NONE_MATCH, etag);
+		}
+		if let Some(last_modified) = &remote.last_modified {
+			request = request.header(IF_MODIFIED_SINCE, last_modified);
+		}
+	}
+
+	let resp = request.send().await?;
+	let userinfo = header(resp.headers(), SUBSCRIPTION_USERINFO)
+		.as_deref()
+		.and_then(SubscriptionInfo::from_userinfo_header);
+	if resp.status() == StatusCode::NOT_MODIFIED {
+		return Ok(Fetched {
+			outcome: FetchOutcome::NotModified,
+			userinfo,
+		});
+	}
+	let resp = resp.error_for_status()?;
+	let etag = header(resp.headers(), ETAG);
+	let last_modified = header(resp.headers(), LAST_MODIFIED);
+
+	let body = resp.bytes().await?;
+	let text = String::from_utf8_lossy(&body);
+	if text.trim().is_empty() {
+		return Err(eyre::eyre!("the subscription returned an empty profile"));
+	}
+	// Expired or mistyped links often land on an HTML error page
+	if text.trim_start().starts_with('<') {
+		return Err(eyre::eyre!("the subscription returned a web page instead of a profile"));
+	}
+
+	std::fs::create_dir_all(config_dir)?;
+	let tmp = path.with_extension("yaml.tmp");
+	tokio::fs::write(&tmp, &body).await?;
+	tokio::fs::rename(&tmp, &path).await?;
+	tracing::info!("Downloaded profile {:?} to {:?}", profile.name, path);
+
+	Ok(Fetched {
+		outcome: FetchOutcome::Updated { etag, last_modified },
+		userinfo,
+	})
+}
+
+fn header(headers: &HeaderMap, name: HeaderName) -> Option<String> {
+	headers.get(name).and_then(|value| value.to_str().ok()).map(str::to_string)
+}
+
+/// Seconds since the Unix epoch, the unit stored in the index.
+pub fn unix_now() -> i64 {
+	SystemTime::now()
+		.duration_since(UNIX_EPOCH)
+		.map(|elapsed| elapsed.as_secs() as i64)
+		.unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+	use std::sync::{Arc, Mutex};
+
+	use tokio::{
+		io::{AsyncReadExt, AsyncWriteExt},
+		net::TcpListener,
+	};
+
+	use super::*;
+
+	/// Answers one connection after another with `responses` and returns the
+	/// base URL along with the request heads it saw.
+	async fn serve(responses: Vec<String>) -> (String, Arc<Mutex<Vec<String>>>) {
+		let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+		let addr = listener.local_addr().unwrap();
+		let requests = Arc::new(Mutex::new(Vec::new()));
+		let seen = requests.clone();
+		tokio::spawn(async move {
+			for response in responses {
+				let Ok((mut socket, _)) = listener.accept().await else {
+					break;
+				};
+				let mut request = Vec::new();
+				let mut buf = [0; 1024];
+				while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+					match socket.read(&mut buf).await {
+						Ok(0) | Err(_) => break,
+						Ok(n) => request.extend_from_slice(&buf[..n]),
+					}
+				}
+				seen.lock().unwrap().push(String::from_utf8_lossy(&request).to_ascii_lowercase());
+				let _ = socket.write_all(response.as_bytes()).await;
+			}
+		});
+		(format!("http://{}", addr), requests)
+	}
+
+	fn ok(headers: &str, body: &str) -> String {
+		format!(
+			"HTTP/1.1 200 OK\r\n{}Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+			headers,
+			body.len(),
+			body
+		)
+	}
+
+	fn remote(url: String) -> Profile {
+		let source = ProfileSource::Remote(RemoteSource::new(url, None, DEFAULT_REFRESH_HOURS));
+		Profile::new("sub".to_string(), "Sub".to_string(), source, 0)
+	}
+
+	#[tokio::test]
+	async fn downloads_then_revalidates_with_the_etag() {
+		let dir = tempfile::tempdir().unwrap();
+		let body = "proxies: []\n";
+		let (url, requests) = serve(vec![
+			ok(
+				"ETag: \"v1\"\r\nLast-Modified: Sat, 17 Oct 2026 08:00:00 GMT\r\n\
+				 Subscription-Userinfo: upload=1; download=2; total=10\r\n",
+				body,
+			),
+			"HTTP/1.1 304 Not Modified\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_string(),
+		])
+		.await;
+		let mut profile = remote(format!("{}/sub", url));
+
+		let fetched = fetch(&reqwest::Client::new(), &profile, dir.path()).await.unwrap();
+		assert_eq!(
+			fetched.outcome,
+			FetchOutcome::Updated {
+				etag: Some("\"v1\"".to_string()),
+				last_modified: Some("Sat, 17 Oct 2026 08:00:00 GMT".to_string()),
+			}
+		);
+		assert_eq!(fetched.userinfo.map(|info| info.total), Some(10));
+		assert_eq!(std::fs::read_to_string(dir.path().join("sub.yaml")).unwrap(), body);
+		assert!(!dir.path().join("sub.yaml.tmp").exists());
+		assert!(requests.lock().unwrap()[0].contains(&format!("user-agent: {}", DEFAULT_USER_AGENT)));
+		assert!(!requests.lock().unwrap()[0].contains("if-none-match"));
+
+		profile.remote_mut().unwrap().apply(&fetched, 1);
+		let fetched = fetch(&reqwest::Client::new(), &profile, dir.path()).await.unwrap();
+		assert_eq!(fetched.outcome, FetchOutcome::NotModified);
+		assert!(requests.lock().unwrap()[1].contains("if-none-match: \"v1\""));
+		assert_eq!(std::fs::read_to_string(dir.path().join("sub.yaml")).unwrap(), body);
+	}
+
+	#[test]
+	fn userinfo_is_stored_in_snake_case() {
+		let mut remote = RemoteSource::new("https://example.com".to_string(), None, 0);
+		let info = SubscriptionInfo {
+			upload: 1,
+			download: 2,
+			total: 3,
+			expire: 4,
+		};
+		remote.apply(
+			&Fetched {
+				outcome: FetchOutcome::NotModified,
+				userinfo: Some(info),
+			},
+			0,
+		);
+		let json = serde_json::to_value(remote.userinfo).unwrap();
+		assert_eq!(json, serde_json::json!({ "upload": 1, "download": 2, "total": 3, "expire": 4 }));
+		assert_eq!(remote.quota(), Some(info));
+	}
+
+	#[test]
+	fn a_corrupt_index_is_moved_aside() {
+		let dir = tempfile::tempdir().unwrap();
+		let path = dir.path().join("profiles.json");
+		assert_eq!(ProfileIndex::load_from(&path).unwrap(), ProfileIndex::default());
+
+		std::fs::write(&path, "{ \"profiles\": [").unwrap();
+		let err = ProfileIndex::load_from(&path).unwrap_err();
+		assert!(err.to_string().contains("profiles.json.corrupt"), "{}", err);
+		assert!(!path.exists());
+		assert_eq!(std::fs::read_to_string(dir.path().join("profiles.json.corrupt")).unwrap(), "{ \"profiles\": [");
+	}
+
+	#[test]
+	fn index_round_trips() {
+		let dir = tempfile::tempdir().unwrap();
+		let path = dir.path().join("profiles.json");
+		let mut index = ProfileIndex::default();
+		index.insert(remote("https://example.com/sub".to_string()));
+		std::fs::write(&path, serde_json::to_string(&index).unwrap()).unwrap();
+		assert_eq!(ProfileIndex::load_from(&path).unwrap(), index);
+	}
+
+	#[tokio::test]
+	async fn rejects_web_pages_and_empty_bodies() {
+		let dir = tempfile::tempdir().unwrap();
+		let (url, _) = serve(vec![ok("", "\n  <!DOCTYPE html><html></html>"), ok("", " \n")]).await;
+		let profile = remote(format!("{}/sub", url));
+
+		let err = fetch(&reqwest::Client::new(), &profile, dir.path()).await.unwrap_err();
+		assert!(err.to_string().contains("web page"), "{}", err);
+		let err = fetch(&reqwest::Client::new(), &profile, dir.path()).await.unwrap_err();
+		assert!(err.to_string().contains("empty"), "{}", err);
+		assert!(!dir.path().join("sub.yaml").exists());
+	}
+
+	#[tokio::test]
+	async fn keeps_the_old_file_when_the_server_fails() {
+		let dir = tempfile::tempdir().unwrap();
+		std::fs::write(dir.path().join("sub.yaml"), "old\n").unwrap();
+		let (url, _) = serve(vec![
+			"HTTP/1.1 500 Internal Server Error\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_string(),
+		])
+		.await;
+
+		assert!(fetch(&reqwest::Client::new(), &remote(format!("{}/sub", url)), dir.path()).await.is_err());
+		assert_eq!(std::fs::read_to_string(dir.path().join("sub.yaml")).unwrap(), "old\n");
+	}
+}
@@ -0,0 +1,189 @@
+// SPDX-License-Identifier: AGPL3.0
+
+use std::time::Duration;
+
+use tokio::{sync::watch, time::sleep};
+
+use crate::{
+	api::{ClashApi, ClashApiError, Version},
+	preflight::ConfigIssue,
+	sidecar::SidecarStatus,
+};
+
+/// How long a freshly spawned core gets to bring up its controller.
+pub const READY_TIMEOUT: Duration = Duration::from_secs(15);
+
+const INITIAL_POLL_DELAY: Duration = Duration::from_millis(100);
+const MAX_POLL_DELAY: Duration = Duration::from_secs(1);
+
+/// Startup phase of the core as seen through its controller.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Readiness {
+	Stopped,
+	Starting,
+	Ready,
+	/// Asked to stop; a new start waits until the old core has exited.
+	Stopping,
+	Failed(ReadinessError),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReadinessError {
+	/// The core rejected the config in test mode.
+	InvalidConfig(ConfigIssue),
+	/// The core binary could not be launched at all.
+	Spawn(String),
+	/// The core went away before its controller answered.
+	Exited { exit_code: Option<i32> },
+	/// The controller did not answer before the deadline.
+	Timeout(Duration),
+	/// The controller answered but refused us, e.g. a wrong secret.
+	Api(ClashApiError),
+}
+
+impl std::fmt::Display for ReadinessError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::InvalidConfig(issue) => write!(f, "invalid config: {}", issue),
+			Self::Spawn(reason) => write!(f, "failed to launch core: {}", reason),
+			Self::Exited { exit_code: Some(code) } => write!(f, "core exited with code {}", code),
+			Self::Exited { exit_code: None } => write!(f, "core exited"),
+			Self::Timeout(timeout) => write!(f, "controller did not respond within {:?}", timeout),
+			Self::Api(err) => write!(f, "{}", err),
+		}
+	}
+}
+
+impl std::error::Error for ReadinessError {}
+
+/// Polls `/version` with backoff until the controller answers, the core exits
+/// or `timeout` elapses.
+pub async fn wait_until_ready(
+	api: ClashApi,
+	mut status: watch::Receiver<SidecarStatus>,
+	timeout: Duration,
+) -> Result<Version, ReadinessError> {
+	let probe = async {
+		let mut delay = INITIAL_POLL_DELAY;
+		loop {
+			match api.version().await {
+				Ok(version) => return Ok(version),
+				// Retrying will not fix a wrong secret
+				Err(err @ ClashApiError::Unauthorized) => return Err(err),
+				Err(err) => tracing::debug!("Clash controller not ready yet: {}", err),
+			}
+			sleep(delay).await;
+			delay = (delay * 2).min(MAX_POLL_DELAY);
+		}
+	};
+
+	let exited = async {
+		loop {
+			let current = status.borrow_and_update().clone();
+			match current {
+				SidecarStatus::Running { .. } => {}
+				SidecarStatus::Restarting { exit_code, .. } | SidecarStatus::Crashed { exit_code } => {
+					return exit_code;
+				}
+				SidecarStatus::Stopped => return None,
+			}
+			if status.changed().await.is_err() {
+				return None;
+			}
+		}
+	};
+
+	tokio::select! {
+		version = probe => version.map_err(ReadinessError::Api),
+		exit_code = exited => Err(ReadinessError::Exited { exit_code }),
+		_ = sleep(timeout) => Err(ReadinessError::Timeout(timeout)),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::sync::{
+		Arc,
+		atomic::{AtomicUsize, Ordering},
+	};
+
+	use tokio::{
+		io::{AsyncReadExt, AsyncWriteExt},
+		net::TcpListener,
+	};
+
+	use super::*;
+
+	/// A controller that answers `/version` with 503 for its first `failures`
+	/// requests and with a version after that. Returns the base URL and the
+	/// number of requests seen.
+	async fn controller(failures: usize) -> (String, Arc<AtomicUsize>) {
+		let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+		let addr = listener.local_addr().unwrap();
+		let requests = Arc::new(AtomicUsize::new(0));
+		let seen = requests.clone();
+		tokio::spawn(async move {
+			while let Ok((mut socket, _)) = listener.accept().await {
+				let mut request = Vec::new();
+				let mut buf = [0; 1024];
+				while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+					match socket.read(&mut buf).await {
+						Ok(0) | Err(_) => break,
+						Ok(n) => request.extend_from_slice(&buf[..n]),
+					}
+				}
+				let (status, body) = if seen.fetch_add(1, Ordering::SeqCst) < failures {
+					("503 Service Unavailable", "")
+				} else {
+					("200 OK", r#"{"version":"0.7.1"}"#)
+				};
+				let response = format!(
+					"HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+					status,
+					body.len(),
+					body
+				);
+				let _ = socket.write_all(response.as_bytes()).await;
+			}
+		});
+		(format!("http://{}", addr), requests)
+	}
+
+	fn running() -> (watch::Sender<SidecarStatus>, watch::Receiver<SidecarStatus>) {
+		watch::channel(SidecarStatus::Running { pid: Some(1) })
+	}
+
+	#[tokio::test]
+	async fn ready_once_the_controller_answers() {
+		let (url, requests) = controller(2).await;
+		let (_status, receiver) = running();
+
+		let version = wait_until_ready(ClashApi::new(url, None), receiver, Duration::from_secs(5))
+			.await
+			.unwrap();
+		assert_eq!(version.version.as_deref(), Some("0.7.1"));
+		assert_eq!(requests.load(Ordering::SeqCst), 3);
+	}
+
+	#[tokio::test]
+	async fn gives_up_after_the_timeout() {
+		let (url, requests) = controller(usize::MAX).await;
+		let (_status, receiver) = running();
+
+		let timeout = Duration::from_millis(400);
+		let err = wait_until_ready(ClashApi::new(url, None), receiver, timeout).await.unwrap_err();
+		assert_eq!(err, ReadinessError::Timeout(timeout));
+		assert!(requests.load(Ordering::SeqCst) >= 2);
+	}
+
+	#[tokio::test]
+	async fn stops_waiting_when_the_core_exits() {
+		let (url, _) = controller(usize::MAX).await;
+		let (status, receiver) = running();
+
+		let waiting = tokio::spawn(wait_until_ready(ClashApi::new(url, None), receiver, Duration::from_secs(5)));
+		status.send_replace(SidecarStatus::Crashed { exit_code: Some(1) });
+		let err = waiting.await.unwrap().unwrap_err();
+		assert_eq!(err, ReadinessError::Exited { exit_code: Some(1) });
+	}
+}
@@ -1,32 +1,198 @@
 // SPDX-License-Identifier: AGPL3.0
 
 use std::{
-	path::PathBuf,
-	process::{Child, Command},
+	collections::VecDeque,
+	path::{Path, PathBuf},
+	process::Stdio,
+	sync::{
+		Arc, Mutex,
+		atomic::{AtomicU64, Ordering},
+	},
+	time::{Duration, Instant},
 };
 
+use futures_util::{
+	FutureExt, Stream,
+	future::{BoxFuture, Shared},
+};
+use tokio::{
+	io::{AsyncBufReadExt, AsyncRead, BufReader},
+	process::{Child, Command},
+	sync::{oneshot, watch},
+	task::JoinHandle,
+};
 use tracing;
 
+use crate::core_info::CoreFlavour;
+
+/// Monotonic id handed to every supervised session, so subscriptions can tell
+/// two sessions apart.
+static NEXT_SESSION_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Number of output lines kept from the core.
+const OUTPUT_CAPACITY: usize = 500;
+
+/// File in the work dir recording the pid of the core we spawned.
+const PIDFILE_NAME: &str = "cosmic-clash.pid";
+
+/// Default time the core gets to exit after SIGTERM before it is killed.
+pub const DEFAULT_GRACE_PERIOD: Duration = Duration::from_secs(5);
+
+/// Lifecycle state of the supervised clash core.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SidecarStatus {
+	Stopped,
+	Running { pid: Option<u32> },
+	/// The core exited unexpectedly and is waiting out its backoff.
+	Restarting { attempt: u32, exit_code: Option<i32> },
+	/// The core kept crashing and the retry cap was reached.
+	Crashed { exit_code: Option<i32> },
+}
+
+/// How the supervisor reacts when the core exits on its own.
+#[derive(Debug, Clone, Copy)]
+pub struct RestartPolicy {
+	pub max_restarts: u32,
+	pub initial_backoff: Duration,
+	pub max_backoff: Duration,
+	/// Uptime after which the attempt counter resets.
+	pub stable_after: Duration,
+}
+
+impl Default for RestartPolicy {
+	fn default() -> Self {
+		Self {
+			max_restarts: 5,
+			initial_backoff: Duration::from_millis(500),
+			max_backoff: Duration::from_secs(30),
+			stable_after: Duration::from_secs(60),
+		}
+	}
+}
+
+impl RestartPolicy {
+	/// Delay before restart number `attempt` (starting at 1).
+	pub fn backoff(&self, attempt: u32) -> Duration {
+		let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+		self.initial_backoff.saturating_mul(factor).min(self.max_backoff)
+	}
+}
+
+/// Which pipe of the core a line came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputStream {
+	Stdout,
+	Stderr,
+}
+
+#[derive(Debug, Clone)]
+pub struct OutputLine {
+	pub stream: OutputStream,
+	pub text: String,
+}
+
+/// Bounded ring buffer with the most recent lines the core printed.
+#[derive(Debug, Clone)]
+pub struct OutputBuffer {
+	lines: Arc<Mutex<VecDeque<OutputLine>>>,
+	capacity: usize,
+}
+
+impl OutputBuffer {
+	pub fn new(capacity: usize) -> Self {
+		Self {
+			lines: Arc::new(Mutex::new(VecDeque::with_capacity(capacity))),
+			capacity,
+		}
+	}
+
+	fn push(&self, line: OutputLine) {
+		let mut lines = self.lines.lock().unwrap_or_else(|e| e.into_inner());
+		if lines.len() == self.capacity {
+			lines.pop_front();
+		}
+		lines.push_back(line);
+	}
+
+	/// Returns up to `count` of the latest lines, oldest first.
+	pub fn tail(&self, count: usize) -> Vec<OutputLine> {
+		let lines = self.lines.lock().unwrap_or_else(|e| e.into_inner());
+		lines.iter().skip(lines.len().saturating_sub(count)).cloned().collect()
+	}
+
+	pub fn clear(&self) {
+		self.lines.lock().unwrap_or_else(|e| e.into_inner()).clear();
+	}
+}
+
+/// Resolves once a stopped core has exited and been reaped. Clones wait for
+/// the same stop, so the app can hold on to one until it exits.
+pub type Stopping = Shared<BoxFuture<'static, ()>>;
+
+/// Receiver side of the supervisor's status channel, tagged with its session id.
+#[derive(Debug, Clone)]
+pub struct StatusWatch {
+	pub session: u64,
+	pub receiver: watch::Receiver<SidecarStatus>,
+}
+
+impl std::hash::Hash for StatusWatch {
+	fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+		self.session.hash(state);
+	}
+}
+
 #[derive(Debug)]
 pub struct SidecarManager {
-	child: Option<Child>,
 	binary_path: PathBuf,
 	work_dir: PathBuf,
 	config_path: PathBuf,
+	flavour: CoreFlavour,
+	policy: RestartPolicy,
+	grace_period: Duration,
+	output: OutputBuffer,
+	session: u64,
+	status: watch::Sender<SidecarStatus>,
+	shutdown: Option<oneshot::Sender<()>>,
+	supervisor: Option<JoinHandle<()>>,
 }
 
 impl SidecarManager {
 	pub fn new(binary_path: PathBuf, work_dir: PathBuf, config_path: PathBuf) -> Self {
+		let (status, _) = watch::channel(SidecarStatus::Stopped);
 		Self {
-			child: None,
 			binary_path,
 			work_dir,
 			config_path,
+			flavour: CoreFlavour::ClashRs,
+			policy: RestartPolicy::default(),
+			grace_period: DEFAULT_GRACE_PERIOD,
+			output: OutputBuffer::new(OUTPUT_CAPACITY),
+			session: 0,
+			status,
+			shutdown: None,
+			supervisor: None,
 		}
 	}
 
+	/// Which command line the core expects; defaults to clash-rs.
+	pub fn with_flavour(mut self, flavour: CoreFlavour) -> Self {
+		self.flavour = flavour;
+		self
+	}
+
+	pub fn with_restart_policy(mut self, policy: RestartPolicy) -> Self {
+		self.policy = policy;
+		self
+	}
+
+	pub fn with_grace_period(mut self, grace_period: Duration) -> Self {
+		self.grace_period = grace_period;
+		self
+	}
+
 	pub fn start(&mut self) -> eyre::Result<()> {
-		if self.child.is_some() {
+		if self.supervisor.is_some() {
 			tracing::warn!("Clash sidecar is already running");
 			return Ok(());
 		}
@@ -38,42 +204,473 @@ impl SidecarManager {
 			self.config_path
 		);
 
-		let mut cmd = Command::new(&self.binary_path);
-		cmd.arg("-d")
-			.arg(&self.work_dir)
-			.arg("-c")
-			.arg(&self.config_path)
-			.current_dir(&self.work_dir);
+		let spec = LaunchSpec {
+			binary_path: self.binary_path.clone(),
+			work_dir: self.work_dir.clone(),
+			config_path: self.config_path.clone(),
+			flavour: self.flavour,
+			output: self.output.clone(),
+		};
 
-		match cmd.spawn() {
+		let child = match spec.spawn() {
 			Ok(child) => {
 				tracing::info!("Clash sidecar started with pid {:?}", child.id());
-				self.child = Some(child);
-				Ok(())
+				child
 			}
 			Err(err) => {
 				tracing::error!("Failed to start clash sidecar: {}", err);
-				Err(err.into())
+				return Err(err.into());
+			}
+		};
+
+		let (shutdown_tx, shutdown_rx) = oneshot::channel();
+		self.session = NEXT_SESSION_ID.fetch_add(1, Ordering::Relaxed);
+		self.status.send_replace(SidecarStatus::Running { pid: child.id() });
+		self.shutdown = Some(shutdown_tx);
+		self.supervisor = Some(tokio::spawn(supervise(
+			spec,
+			child,
+			self.policy,
+			self.grace_period,
+			self.status.clone(),
+			shutdown_rx,
+		)));
+		Ok(())
+	}
+
+	/// Asks the supervisor to stop the core. The stop goes ahead whether or
+	/// not the returned future is awaited; awaiting it waits out the grace
+	/// period and any kill.
+	pub fn stop(&mut self) -> Stopping {
+		if let Some(shutdown) = self.shutdown.take() {
+			// The supervisor owns the child; it terminates it on request.
+			let _ = shutdown.send(());
+		}
+		let supervisor = self.supervisor.take();
+		async move {
+			if let Some(supervisor) = supervisor
+				&& let Err(err) = supervisor.await
+			{
+				tracing::error!("Clash sidecar supervisor failed: {}", err);
+			}
+		}
+		.boxed()
+		.shared()
+	}
+
+	/// Stops the core and waits until it has exited and been reaped.
+	pub async fn shutdown(mut self) {
+		self.stop().await;
+	}
+
+	/// Captured stdout/stderr of the core, shared across restarts.
+	pub fn output(&self) -> &OutputBuffer {
+		&self.output
+	}
+
+	pub fn status(&self) -> SidecarStatus {
+		self.status.borrow().clone()
+	}
+
+	/// Watches status changes of the current session.
+	pub fn watch_status(&self) -> StatusWatch {
+		StatusWatch {
+			session: self.session,
+			receiver: self.status.subscribe(),
+		}
+	}
+
+	pub fn is_running(&self) -> bool {
+		matches!(*self.status.borrow(), SidecarStatus::Running { .. })
+	}
+}
+
+/// Streams every status change published by the supervisor.
+pub fn status_stream(watch: &StatusWatch) -> impl Stream<Item = SidecarStatus> + use<> {
+	futures_util::stream::unfold(watch.receiver.clone(), |mut receiver| async move {
+		receiver.changed().await.ok()?;
+		let status = receiver.borrow_and_update().clone();
+		Some((status, receiver))
+	})
+}
+
+/// Everything needed to (re)spawn the core.
+#[derive(Debug, Clone)]
+struct LaunchSpec {
+	binary_path: PathBuf,
+	work_dir: PathBuf,
+	config_path: PathBuf,
+	flavour: CoreFlavour,
+	output: OutputBuffer,
+}
+
+impl LaunchSpec {
+	fn spawn(&self) -> std::io::Result<Child> {
+		let mut cmd = Command::new(&self.binary_path);
+		cmd.args(self.flavour.config_args(&self.work_dir, &self.config_path))
+			.current_dir(&self.work_dir)
+			.stdin(Stdio::null())
+			.stdout(Stdio::piped())
+			.stderr(Stdio::piped())
+			.kill_on_drop(true);
+		let mut child = cmd.spawn()?;
+		if let Some(pid) = child.id()
+			&& let Err(err) = std::fs::write(pidfile_path(&self.work_dir), pid.to_string())
+		{
+			tracing::warn!("Failed to write clash sidecar pidfile: {}", err);
+		}
+
+		if let Some(stdout) = child.stdout.take() {
+			tokio::spawn(capture_output(stdout, OutputStream::Stdout, self.output.clone()));
+		}
+		if let Some(stderr) = child.stderr.take() {
+			tokio::spawn(capture_output(stderr, OutputStream::Stderr, self.output.clone()));
+		}
+		Ok(child)
+	}
+}
+
+/// Copies one pipe of the core into the output buffer and the `clash_lib`
+/// tracing target until the pipe closes.
+async fn capture_output<R>(pipe: R, stream: OutputStream, output: OutputBuffer)
+where
+	R: AsyncRead + Unpin,
+{
+	let mut reader = BufReader::new(pipe);
+	let mut buf = Vec::new();
+	loop {
+		buf.clear();
+		match reader.read_until(b'\n', &mut buf).await {
+			Ok(0) => break,
+			Ok(_) => {
+				// Keep draining on bytes that are not UTF-8, or the core blocks on a full pipe
+				let text = String::from_utf8_lossy(&buf).trim_end_matches(['\n', '\r']).to_string();
+				match stream {
+					OutputStream::Stdout => tracing::info!(target: "clash_lib", "{}", text),
+					OutputStream::Stderr => tracing::warn!(target: "clash_lib", "{}", text),
+				}
+				output.push(OutputLine { stream, text });
+			}
+			Err(err) => {
+				tracing::warn!("Failed to read clash sidecar output: {}", err);
+				break;
 			}
 		}
 	}
+}
 
-	pub fn stop(&mut self) -> eyre::Result<()> {
-		if let Some(mut child) = self.child.take() {
-			match child.kill() {
-				Ok(_) => {
-					tracing::info!("Clash sidecar killed successfully");
+/// Reaps the core and restarts it with exponential backoff until the retry
+/// cap is hit or a shutdown is requested.
+async fn supervise(
+	spec: LaunchSpec,
+	child: Child,
+	policy: RestartPolicy,
+	grace_period: Duration,
+	status: watch::Sender<SidecarStatus>,
+	mut shutdown: oneshot::Receiver<()>,
+) {
+	let _pidfile = PidfileGuard(pidfile_path(&spec.work_dir));
+	let mut child = Some(child);
+	let mut attempt = 0;
+	loop {
+		let started = Instant::now();
+		let exit_code = match child.as_mut() {
+			Some(running) => tokio::select! {
+				exit = running.wait() => match exit {
+					Ok(exit) => exit.code(),
+					Err(err) => {
+						tracing::error!("Failed to wait on clash sidecar: {}", err);
+						None
+					}
+				},
+				_ = &mut shutdown => {
+					terminate(running, grace_period).await;
+					status.send_replace(SidecarStatus::Stopped);
+					return;
 				}
-				Err(err) => {
-					tracing::error!("Failed to kill clash sidecar: {}", err);
-					return Err(err.into());
+			},
+			// The previous respawn failed outright.
+			None => None,
+		};
+
+		// A core that stayed up for a while earns a fresh retry budget.
+		if started.elapsed() >= policy.stable_after {
+			attempt = 0;
+		}
+		attempt += 1;
+		if attempt > policy.max_restarts {
+			tracing::error!(
+				"Clash sidecar exited with {:?}, giving up after {} restarts",
+				exit_code,
+				policy.max_restarts
+			);
+			status.send_replace(SidecarStatus::Crashed { exit_code });
+			return;
+		}
+
+		let delay = policy.backoff(attempt);
+		tracing::warn!(
+			"Clash sidecar exited with {:?}, restarting in {:?} (attempt {})",
+			exit_code,
+			delay,
+			attempt
+		);
+		status.send_replace(SidecarStatus::Restarting { attempt, exit_code });
+
+		tokio::select! {
+			_ = tokio::time::sleep(delay) => {}
+			_ = &mut shutdown => {
+				status.send_replace(SidecarStatus::Stopped);
+				return;
+			}
+		}
+
+		child = match spec.spawn() {
+			Ok(next) => {
+				tracing::info!("Clash sidecar restarted with pid {:?}", next.id());
+				status.send_replace(SidecarStatus::Running { pid: next.id() });
+				Some(next)
+			}
+			Err(err) => {
+				tracing::error!("Failed to restart clash sidecar: {}", err);
+				None
+			}
+		};
+	}
+}
+
+/// Removes the pidfile once the supervisor is done with the core.
+struct PidfileGuard(PathBuf);
+
+impl Drop for PidfileGuard {
+	fn drop(&mut self) {
+		remove_pidfile(&self.0);
+	}
+}
+
+pub fn pidfile_path(work_dir: &Path) -> PathBuf {
+	work_dir.join(PIDFILE_NAME)
+}
+
+fn remove_pidfile(path: &Path) {
+	if let Err(err) = std::fs::remove_file(path)
+		&& err.kind() != std::io::ErrorKind::NotFound
+	{
+		tracing::warn!("Failed to remove clash sidecar pidfile: {}", err);
+	}
+}
+
+/// Returns the pid recorded in `work_dir` if that process is still alive and
+/// runs `binary`.
+pub fn running_pid(work_dir: &Path, binary: &Path) -> Option<u32> {
+	let path = pidfile_path(work_dir);
+	let pid: u32 = std::fs::read_to_string(&path).ok()?.trim().parse().ok()?;
+	if !process_alive(pid) {
+		// Left behind by a core that is gone; nothing to adopt
+		remove_pidfile(&path);
+		return None;
+	}
+	// The pid may have been reused by an unrelated process
+	runs_binary(pid, binary).then_some(pid)
+}
+
+/// Whether `pid` is an instance of `binary`, so a reused pid is never
+/// signalled.
+#[cfg(target_os = "linux")]
+fn runs_binary(pid: u32, binary: &Path) -> bool {
+	let Ok(exe) = std::fs::read_link(format!("/proc/{}/exe", pid)) else {
+		return false;
+	};
+	// A binary replaced since it was started shows up as `<path> (deleted)`
+	let exe = exe
+		.to_str()
+		.and_then(|exe| exe.strip_suffix(" (deleted)"))
+		.map(PathBuf::from)
+		.unwrap_or(exe);
+	let binary = std::fs::canonicalize(binary).unwrap_or_else(|_| binary.to_path_buf());
+	exe == binary
+}
+
+/// Without `/proc` the process cannot be identified, so it is left alone.
+#[cfg(not(target_os = "linux"))]
+fn runs_binary(_pid: u32, _binary: &Path) -> bool {
+	false
+}
+
+#[cfg(unix)]
+fn process_alive(pid: u32) -> bool {
+	// SAFETY: signal 0 only checks whether the pid exists.
+	unsafe { libc::kill(pid as libc::pid_t, 0) == 0 }
+}
+
+#[cfg(not(unix))]
+fn process_alive(_pid: u32) -> bool {
+	false
+}
+
+/// Stops a core that is not our child (e.g. left over from a previous run):
+/// SIGTERM, wait up to `grace_period`, then SIGKILL. Does nothing unless `pid`
+/// still runs `binary`.
+pub async fn terminate_pid(pid: u32, binary: PathBuf, work_dir: PathBuf, grace_period: Duration) {
+	#[cfg(unix)]
+	if runs_binary(pid, &binary) {
+		// SAFETY: plain signal delivery to a pid that was checked to run the core.
+		unsafe { libc::kill(pid as libc::pid_t, libc::SIGTERM) };
+		let deadline = Instant::now() + grace_period;
+		while process_alive(pid) && Instant::now() < deadline {
+			tokio::time::sleep(Duration::from_millis(100)).await;
+		}
+		if process_alive(pid) {
+			tracing::warn!("Clash core {} ignored SIGTERM for {:?}, killing it", pid, grace_period);
+			// SAFETY: as above.
+			unsafe { libc::kill(pid as libc::pid_t, libc::SIGKILL) };
+		}
+	} else {
+		tracing::warn!("Process {} no longer runs {:?}, leaving it alone", pid, binary);
+	}
+	#[cfg(not(unix))]
+	tracing::warn!("Cannot terminate clash core {} ({:?}) on this platform", pid, binary);
+
+	remove_pidfile(&pidfile_path(&work_dir));
+}
+
+/// Sends SIGTERM so the core can tear down TUN and DNS, then falls back to
+/// SIGKILL once the grace period runs out. Either way the child is reaped.
+async fn terminate(child: &mut Child, grace_period: Duration) {
+	#[cfg(unix)]
+	if let Some(pid) = child.id() {
+		// SAFETY: `pid` belongs to our own child, which has not been reaped yet.
+		if unsafe { libc::kill(pid as libc::pid_t, libc::SIGTERM) } == 0 {
+			match tokio::time::timeout(grace_period, child.wait()).await {
+				Ok(Ok(exit)) => {
+					tracing::info!("Clash sidecar exited gracefully with {}", exit);
+					return;
 				}
+				Ok(Err(err)) => tracing::error!("Failed to wait on clash sidecar: {}", err),
+				Err(_) => tracing::warn!("Clash sidecar ignored SIGTERM for {:?}, killing it", grace_period),
 			}
 		}
-		Ok(())
 	}
 
-	pub fn is_running(&self) -> bool {
-		self.child.as_ref().map(|c| c.id()).is_some()
+	match child.kill().await {
+		Ok(()) => tracing::info!("Clash sidecar killed successfully"),
+		Err(err) => tracing::error!("Failed to kill clash sidecar: {}", err),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	#[cfg(unix)]
+	use std::os::unix::process::ExitStatusExt;
+
+	use super::*;
+
+	#[tokio::test]
+	async fn capture_output_keeps_reading_past_invalid_utf8() {
+		let output = OutputBuffer::new(10);
+		let pipe: &[u8] = b"first\nbad \xff byte\r\nlast";
+		capture_output(pipe, OutputStream::Stdout, output.clone()).await;
+		let lines: Vec<String> = output.tail(10).into_iter().map(|line| line.text).collect();
+		assert_eq!(lines, ["first", "bad \u{fffd} byte", "last"]);
+	}
+
+	#[test]
+	fn output_buffer_drops_the_oldest_lines() {
+		let output = OutputBuffer::new(2);
+		for text in ["a", "b", "c"] {
+			output.push(OutputLine {
+				stream: OutputStream::Stderr,
+				text: text.to_string(),
+			});
+		}
+		let lines: Vec<String> = output.tail(5).into_iter().map(|line| line.text).collect();
+		assert_eq!(lines, ["b", "c"]);
+	}
+
+	#[cfg(target_os = "linux")]
+	#[test]
+	fn running_pid_checks_the_binary() {
+		let dir = tempfile::tempdir().unwrap();
+		let core = copied_sleep(dir.path());
+		let mut child = std::process::Command::new(&core).arg("30").spawn().unwrap();
+		std::fs::write(pidfile_path(dir.path()), child.id().to_string()).unwrap();
+
+		assert_eq!(running_pid(dir.path(), &core), Some(child.id()));
+		// A reused pid running something else is not offered
+		assert_eq!(running_pid(dir.path(), Path::new("/bin/sleep")), None);
+		assert!(pidfile_path(dir.path()).exists());
+
+		child.kill().unwrap();
+		child.wait().unwrap();
+		assert_eq!(running_pid(dir.path(), &core), None);
+		assert!(!pidfile_path(dir.path()).exists());
+	}
+
+	/// A private copy of `sleep`, so it is a different file from every system
+	/// binary even where those are one multicall executable.
+	#[cfg(target_os = "linux")]
+	fn copied_sleep(dir: &Path) -> PathBuf {
+		let core = dir.join("clash");
+		std::fs::copy("/bin/sleep", &core).unwrap();
+		core
+	}
+
+	#[cfg(target_os = "linux")]
+	#[tokio::test]
+	async fn terminate_pid_leaves_other_binaries_alone() {
+		let dir = tempfile::tempdir().unwrap();
+		let core = copied_sleep(dir.path());
+		let mut child = std::process::Command::new(&core).arg("30").spawn().unwrap();
+		terminate_pid(child.id(), PathBuf::from("/bin/sleep"), dir.path().to_path_buf(), Duration::ZERO).await;
+		assert!(child.try_wait().unwrap().is_none());
+
+		// Our own unreaped child lingers as a zombie, so keep the grace period short
+		terminate_pid(child.id(), core, dir.path().to_path_buf(), Duration::from_millis(200)).await;
+		assert_eq!(child.wait().unwrap().signal(), Some(libc::SIGTERM));
+	}
+
+	#[cfg(unix)]
+	#[tokio::test]
+	async fn stop_waits_for_the_core_to_exit() {
+		use std::os::unix::fs::PermissionsExt;
+
+		let dir = tempfile::tempdir().unwrap();
+		let core = dir.path().join("clash");
+		std::fs::write(&core, "#!/bin/sh\nexec sleep 30\n").unwrap();
+		std::fs::set_permissions(&core, std::fs::Permissions::from_mode(0o755)).unwrap();
+		let mut manager = SidecarManager::new(core, dir.path().to_path_buf(), dir.path().join("config.yaml"))
+			.with_grace_period(Duration::from_secs(5));
+		manager.start().unwrap();
+		assert!(manager.is_running());
+
+		let stopping = manager.stop();
+		let again = stopping.clone();
+		tokio::time::timeout(Duration::from_secs(5), stopping).await.unwrap();
+		// Every clone resolves once the core is gone
+		again.await;
+		assert_eq!(manager.status(), SidecarStatus::Stopped);
+		assert!(!pidfile_path(dir.path()).exists());
+	}
+
+	#[test]
+	fn backoff_doubles_from_the_initial_delay() {
+		let policy = RestartPolicy::default();
+		assert_eq!(policy.backoff(1), Duration::from_millis(500));
+		assert_eq!(policy.backoff(2), Duration::from_secs(1));
+		assert_eq!(policy.backoff(3), Duration::from_secs(2));
+		assert_eq!(policy.backoff(4), Duration::from_secs(4));
+	}
+
+	#[test]
+	fn backoff_is_capped() {
+		let policy = RestartPolicy::default();
+		assert_eq!(policy.backoff(7), Duration::from_secs(30));
+		assert_eq!(policy.backoff(u32::MAX), Duration::from_secs(30));
+	}
+
+	#[test]
+	fn backoff_treats_attempt_zero_like_the_first() {
+		let policy = RestartPolicy::default();
+		assert_eq!(policy.backoff(0), policy.backoff(1));
 	}
 }
@@ -0,0 +1,168 @@
+// SPDX-License-Identifier: AGPL3.0
+
+//! Long-lived controller streams, fed into the app as iced subscriptions.
+
+use std::{
+	pin::pin,
+	time::{Duration, Instant},
+};
+
+use cosmic::iced::futures::{SinkExt, Stream, StreamExt, channel::mpsc};
+
+use crate::{
+	api::{ClashApi, LogLevel},
+	app::Message,
+};
+
+/// Wait before reconnecting a stream the core closed or refused.
+const RECONNECT_DELAY: Duration = Duration::from_secs(2);
+
+/// Minimum spacing between two `TrafficUpdated` messages.
+const TRAFFIC_INTERVAL: Duration = Duration::from_millis(900);
+
+/// How often the connections snapshot is refreshed.
+const CONNECTIONS_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How often the running config is re-read to catch changes made by other
+/// controllers, e.g. a web dashboard.
+const RUNTIME_CONFIG_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Most log lines delivered to the app in one message.
+const LOG_BATCH: usize = 64;
+
+/// How often remote profiles are checked for a due refresh.
+const PROFILE_REFRESH_CHECK: Duration = Duration::from_secs(60);
+
+/// Follows `/traffic`, emitting at most one `Message::TrafficUpdated` per
+/// second and reconnecting whenever the stream drops.
+pub fn traffic(api: &ClashApi) -> impl Stream<Item = Message> + use<> {
+	let api = api.clone();
+	cosmic::iced::stream::channel(16, move |mut output: mpsc::Sender<Message>| async move {
+		loop {
+			let mut samples = pin!(api.traffic_stream());
+			let mut last_sent: Option<Instant> = None;
+			while let Some(sample) = samples.next().await {
+				let message = match sample {
+					Ok(traffic) => {
+						if last_sent.is_some_and(|at| at.elapsed() < TRAFFIC_INTERVAL) {
+							continue;
+						}
+						last_sent = Some(Instant::now());
+						Message::TrafficUpdated(traffic)
+					}
+					Err(err) => Message::StreamFailed(err),
+				};
+				if output.send(message).await.is_err() {
+					return;
+				}
+			}
+			tokio::time::sleep(RECONNECT_DELAY).await;
+		}
+	})
+}
+
+/// Emits a `/connections` snapshot every second. Over plain HTTP the core
+/// answers with a single snapshot (it only streams over websockets), so this
+/// polls instead of holding a connection open.
+pub fn connections(api: &ClashApi) -> impl Stream<Item = Message> + use<> {
+	let api = api.clone();
+	cosmic::iced::stream::channel(4, move |mut output: mpsc::Sender<Message>| async move {
+		let mut interval = tokio::time::interval(CONNECTIONS_INTERVAL);
+		interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+		loop {
+			interval.tick().await;
+			let message = match api.connections().await {
+				Ok(snapshot) => Message::ConnectionsUpdated(snapshot),
+				Err(err) => Message::StreamFailed(err),
+			};
+			let failed = matches!(message, Message::StreamFailed(_));
+			if output.send(message).await.is_err() {
+				return;
+			}
+			if failed {
+				tokio::time::sleep(RECONNECT_DELAY).await;
+			}
+		}
+	})
+}
+
+/// Follows `/memory`, reconnecting whenever the stream drops.
+pub fn memory(api: &ClashApi) -> impl Stream<Item = Message> + use<> {
+	let api = api.clone();
+	cosmic::iced::stream::channel(4, move |mut output: mpsc::Sender<Message>| async move {
+		loop {
+			let mut samples = pin!(api.memory_stream());
+			while let Some(sample) = samples.next().await {
+				let message = match sample {
+					Ok(memory) => Message::MemoryUpdated(memory),
+					Err(err) => Message::StreamFailed(err),
+				};
+				if output.send(message).await.is_err() {
+					return;
+				}
+			}
+			tokio::time::sleep(RECONNECT_DELAY).await;
+		}
+	})
+}
+
+/// Re-reads `/configs` every few seconds.
+pub fn runtime_config(api: &ClashApi) -> impl Stream<Item = Message> + use<> {
+	let api = api.clone();
+	cosmic::iced::stream::channel(4, move |mut output: mpsc::Sender<Message>| async move {
+		let mut interval = tokio::time::interval(RUNTIME_CONFIG_INTERVAL);
+		interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+		loop {
+			interval.tick().await;
+			let message = Message::RuntimeConfigFetched(api.get_configs().await);
+			if output.send(message).await.is_err() {
+				return;
+			}
+		}
+	})
+}
+
+/// Follows `/logs` at the given level. Lines that arrive together are
+/// delivered as one batch, so a chatty debug log does not flood the update
+/// loop.
+pub fn logs(source: &(ClashApi, LogLevel)) -> impl Stream<Item = Message> + use<> {
+	let (api, level) = source.clone();
+	cosmic::iced::stream::channel(16, move |mut output: mpsc::Sender<Message>| async move {
+		loop {
+			let mut batches = pin!(api.logs_stream(level).ready_chunks(LOG_BATCH));
+			while let Some(batch) = batches.next().await {
+				let mut entries = Vec::with_capacity(batch.len());
+				let mut error = None;
+				for item in batch {
+					match item {
+						Ok(entry) => entries.push(entry),
+						Err(err) => error = Some(err),
+					}
+				}
+				if !entries.is_empty() && output.send(Message::LogsReceived(entries)).await.is_err() {
+					return;
+				}
+				if let Some(err) = error {
+					if output.send(Message::StreamFailed(err)).await.is_err() {
+						return;
+					}
+				}
+			}
+			tokio::time::sleep(RECONNECT_DELAY).await;
+		}
+	})
+}
+
+/// Asks the app to refresh due remote profiles, once at startup and then
+/// every minute.
+pub fn profile_refresh() -> impl Stream<Item = Message> + use<> {
+	cosmic::iced::stream::channel(1, move |mut output: mpsc::Sender<Message>| async move {
+		let mut ticks = tokio::time::interval(PROFILE_REFRESH_CHECK);
+		loop {
+			ticks.tick().await;
+			if output.send(Message::RefreshDueProfiles).await.is_err() {
+				return;
+			}
+		}
+	})
+}
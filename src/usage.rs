@@ -0,0 +1,250 @@
+// SPDX-License-Identifier: AGPL3.0
+
+//! Daily traffic totals per profile, kept in a JSON file under the data dir
+//! so they survive restarts.
+
+use std::{
+	collections::BTreeMap,
+	path::{Path, PathBuf},
+	sync::OnceLock,
+	time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+
+/// Longest gap between two samples that still counts as continuous traffic.
+/// Anything longer (suspend, a stalled stream) is clamped to this.
+const MAX_SAMPLE_GAP: Duration = Duration::from_secs(5);
+
+/// Bytes sent and received.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct Usage {
+	pub up: u64,
+	pub down: u64,
+}
+
+impl Usage {
+	pub fn total(&self) -> u64 {
+		self.up + self.down
+	}
+}
+
+impl std::ops::AddAssign for Usage {
+	fn add_assign(&mut self, other: Self) {
+		self.up += other.up;
+		self.down += other.down;
+	}
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UsageStore {
+	/// `YYYY-MM-DD` (local time) to profile name to usage.
+	days: BTreeMap<String, BTreeMap<String, Usage>>,
+}
+
+impl UsageStore {
+	fn path() -> PathBuf {
+		dirs::data_dir()
+			.unwrap_or_else(|| PathBuf::from("."))
+			.join("cosmic-clash")
+			.join("usage.json")
+	}
+
+	/// Reads the store, starting empty if there is none or it is unreadable.
+	/// One that does not parse is moved aside first, so the next save does
+	/// not overwrite the history in it.
+	pub fn load() -> Self {
+		Self::load_from(&Self::path())
+	}
+
+	fn load_from(path: &Path) -> Self {
+		if !path.exists() {
+			return Self::default();
+		}
+		let json = match std::fs::read_to_string(path) {
+			Ok(json) => json,
+			Err(err) => {
+				tracing::warn!("Ignoring unreadable usage store {:?}: {}", path, err);
+				return Self::default();
+			}
+		};
+		match serde_json::from_str(&json) {
+			Ok(store) => store,
+			Err(err) => {
+				let backup = path.with_extension("json.corrupt");
+				match std::fs::rename(path, &backup) {
+					Ok(()) => tracing::warn!("Moved corrupt usage store to {:?}: {}", backup, err),
+					Err(rename_err) => {
+						tracing::warn!("Corrupt usage store {:?} ({}) could not be moved: {}", path, err, rename_err)
+					}
+				}
+				Self::default()
+			}
+		}
+	}
+
+	pub fn save(&self) -> eyre::Result<()> {
+		let path = Self::path();
+		if let Some(parent) = path.parent() {
+			std::fs::create_dir_all(parent)?;
+		}
+		let json = serde_json::to_string(self)?;
+		let tmp = path.with_extension("json.tmp");
+		std::fs::write(&tmp, json)?;
+		std::fs::rename(tmp, path)?;
+		Ok(())
+	}
+
+	/// Books a `/traffic` sample (bytes per second) that covered `elapsed`.
+	pub fn record_rate(&mut self, day: &str, profile: &str, up: u64, down: u64, elapsed: Duration) {
+		let secs = elapsed.min(MAX_SAMPLE_GAP).as_secs_f64();
+		let usage = Usage {
+			up: (up as f64 * secs) as u64,
+			down: (down as f64 * secs) as u64,
+		};
+		if usage.total() == 0 {
+			return;
+		}
+		*self
+			.days
+			.entry(day.to_string())
+			.or_default()
+			.entry(profile.to_string())
+			.or_default() += usage;
+	}
+
+	/// Usage of one day over all profiles.
+	pub fn day(&self, day: &str) -> Usage {
+		let mut sum = Usage::default();
+		for usage in self.days.get(day).into_iter().flat_map(|profiles| profiles.values()) {
+			sum += *usage;
+		}
+		sum
+	}
+
+	/// Usage of a `YYYY-MM` month over all profiles.
+	pub fn month(&self, month: &str) -> Usage {
+		let mut sum = Usage::default();
+		for profiles in self.month_days(month).map(|(_, profiles)| profiles) {
+			for usage in profiles.values() {
+				sum += *usage;
+			}
+		}
+		sum
+	}
+
+	/// Usage of a `YYYY-MM` month per profile, largest first.
+	pub fn month_by_profile(&self, month: &str) -> Vec<(String, Usage)> {
+		let mut totals: BTreeMap<&str, Usage> = BTreeMap::new();
+		for (_, profiles) in self.month_days(month) {
+			for (profile, usage) in profiles {
+				*totals.entry(profile.as_str()).or_default() += *usage;
+			}
+		}
+		let mut totals: Vec<(String, Usage)> = totals
+			.into_iter()
+			.map(|(profile, usage)| (profile.to_string(), usage))
+			.collect();
+		totals.sort_by_key(|(_, usage)| std::cmp::Reverse(usage.total()));
+		totals
+	}
+
+	/// The last `count` days that saw traffic, newest first.
+	pub fn recent_days(&self, count: usize) -> Vec<(String, Usage)> {
+		self.days
+			.keys()
+			.rev()
+			.take(count)
+			.map(|day| (day.clone(), self.day(day)))
+			.collect()
+	}
+
+	fn month_days<'a>(&'a self, month: &'a str) -> impl Iterator<Item = (&'a String, &'a BTreeMap<String, Usage>)> {
+		self.days.iter().filter(move |(day, _)| day.starts_with(month))
+	}
+}
+
+/// The local UTC offset, read once by [`init_local_offset`].
+static LOCAL_OFFSET: OnceLock<time::UtcOffset> = OnceLock::new();
+
+/// Reads the local UTC offset. The platform lookup refuses to run once the
+/// process has more than one thread, so call this first thing in `main`.
+pub fn init_local_offset() {
+	LOCAL_OFFSET.get_or_init(|| {
+		time::UtcOffset::current_local_offset().unwrap_or_else(|err| {
+			tracing::warn!("Counting usage in UTC, the local offset is unknown: {}", err);
+			time::UtcOffset::UTC
+		})
+	});
+}
+
+/// Today's date in local time as `YYYY-MM-DD`.
+pub fn today() -> String {
+	let offset = LOCAL_OFFSET.get().copied().unwrap_or(time::UtcOffset::UTC);
+	let now = time::OffsetDateTime::now_utc().to_offset(offset);
+	format!("{:04}-{:02}-{:02}", now.year(), u8::from(now.month()), now.day())
+}
+
+/// The `YYYY-MM` month of a `YYYY-MM-DD` day.
+pub fn month_of(day: &str) -> &str {
+	day.get(..7).unwrap_or(day)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn a_corrupt_store_is_moved_aside() {
+		let dir = tempfile::tempdir().unwrap();
+		let path = dir.path().join("usage.json");
+		std::fs::write(&path, "{ \"days\": ").unwrap();
+		assert_eq!(UsageStore::load_from(&path), UsageStore::default());
+		assert!(!path.exists());
+		assert_eq!(std::fs::read_to_string(dir.path().join("usage.json.corrupt")).unwrap(), "{ \"days\": ");
+	}
+
+	#[test]
+	fn record_rate_scales_by_elapsed_time() {
+		let mut store = UsageStore::default();
+		store.record_rate("2026-10-17", "work", 100, 200, Duration::from_millis(1500));
+		store.record_rate("2026-10-17", "work", 100, 200, Duration::from_secs(1));
+		assert_eq!(store.day("2026-10-17"), Usage { up: 250, down: 500 });
+	}
+
+	#[test]
+	fn record_rate_clamps_long_gaps() {
+		let mut store = UsageStore::default();
+		store.record_rate("2026-10-17", "work", 10, 1, Duration::from_secs(3600));
+		assert_eq!(store.day("2026-10-17"), Usage { up: 50, down: 5 });
+	}
+
+	#[test]
+	fn record_rate_skips_idle_samples() {
+		let mut store = UsageStore::default();
+		store.record_rate("2026-10-17", "work", 0, 0, Duration::from_secs(1));
+		assert!(store.recent_days(10).is_empty());
+	}
+
+	#[test]
+	fn totals_group_by_day_month_and_profile() {
+		let mut store = UsageStore::default();
+		let second = Duration::from_secs(1);
+		store.record_rate("2026-09-30", "work", 1, 1, second);
+		store.record_rate("2026-10-01", "work", 10, 0, second);
+		store.record_rate("2026-10-02", "home", 0, 100, second);
+		store.record_rate("2026-10-02", "work", 5, 0, second);
+
+		assert_eq!(store.month("2026-10"), Usage { up: 15, down: 100 });
+		assert_eq!(
+			store.month_by_profile("2026-10"),
+			vec![
+				("home".to_string(), Usage { up: 0, down: 100 }),
+				("work".to_string(), Usage { up: 15, down: 0 }),
+			]
+		);
+		let days: Vec<String> = store.recent_days(2).into_iter().map(|(day, _)| day).collect();
+		assert_eq!(days, ["2026-10-02", "2026-10-01"]);
+		assert_eq!(month_of("2026-10-02"), "2026-10");
+	}
+}
@@ -0,0 +1,3 @@
+pub mod traffic_chart;
+
+pub use traffic_chart::traffic_chart;
@@ -0,0 +1,214 @@
+// SPDX-License-Identifier: AGPL3.0
+
+//! Up/down traffic over a rolling time window, drawn on an iced canvas.
+
+use std::{
+	collections::VecDeque,
+	time::{Duration, Instant},
+};
+
+use cosmic::{
+	Element, Renderer, Theme,
+	iced::{
+		Color, Length, Pixels, Point, Rectangle, mouse,
+		widget::canvas::{self, Frame, Geometry, Path, Stroke, Text},
+	},
+};
+
+/// Longest window the chart offers; older samples are dropped.
+pub const HISTORY_SPAN: Duration = Duration::from_secs(60 * 60);
+
+/// Lowest top of the y axis, so an idle link does not draw noise full height.
+const MIN_SCALE: f32 = 16.0 * 1024.0;
+
+/// One `/traffic` sample with the time it arrived.
+#[derive(Debug, Clone, Copy)]
+pub struct TrafficSample {
+	pub at: Instant,
+	pub up: u64,
+	pub down: u64,
+}
+
+/// Time range shown by the chart.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ChartWindow {
+	#[default]
+	OneMinute,
+	TenMinutes,
+	OneHour,
+}
+
+impl ChartWindow {
+	pub const ALL: [ChartWindow; 3] = [Self::OneMinute, Self::TenMinutes, Self::OneHour];
+
+	pub fn duration(self) -> Duration {
+		match self {
+			Self::OneMinute => Duration::from_secs(60),
+			Self::TenMinutes => Duration::from_secs(10 * 60),
+			Self::OneHour => HISTORY_SPAN,
+		}
+	}
+}
+
+/// Appends `sample` and forgets whatever fell out of [`HISTORY_SPAN`].
+pub fn record(history: &mut VecDeque<TrafficSample>, sample: TrafficSample) {
+	history.push_back(sample);
+	while history
+		.front()
+		.is_some_and(|oldest| sample.at.duration_since(oldest.at) > HISTORY_SPAN)
+	{
+		history.pop_front();
+	}
+}
+
+/// Canvas program drawing the samples of one window.
+struct TrafficChart<'a> {
+	history: &'a VecDeque<TrafficSample>,
+	window: Duration,
+	peak_label: fn(u64) -> String,
+}
+
+pub fn traffic_chart<'a, Message: 'a>(
+	history: &'a VecDeque<TrafficSample>,
+	window: ChartWindow,
+	height: f32,
+	peak_label: fn(u64) -> String,
+) -> Element<'a, Message> {
+	canvas::Canvas::new(TrafficChart {
+		history,
+		window: window.duration(),
+		peak_label,
+	})
+	.width(Length::Fill)
+	.height(Length::Fixed(height))
+	.into()
+}
+
+impl<Message> canvas::Program<Message, Theme, Renderer> for TrafficChart<'_> {
+	type State = ();
+
+	fn draw(
+		&self,
+		_state: &Self::State,
+		renderer: &Renderer,
+		theme: &Theme,
+		bounds: Rectangle,
+		_cursor: mouse::Cursor,
+	) -> Vec<Geometry<Renderer>> {
+		let mut frame = Frame::new(renderer, bounds.size());
+		let cosmic = theme.cosmic();
+		let grid_color: Color = cosmic.on_bg_component_color().into();
+		let up_color: Color = cosmic.success_color().into();
+		let down_color: Color = cosmic.accent_color().into();
+
+		let now = Instant::now();
+		let samples: Vec<&TrafficSample> = self
+			.history
+			.iter()
+			.filter(|sample| now.duration_since(sample.at) <= self.window)
+			.collect();
+
+		let peak = samples.iter().copied().max_by_key(|sample| sample.up.max(sample.down));
+		let scale = peak
+			.map(|sample| sample.up.max(sample.down) as f32)
+			.unwrap_or_default()
+			.max(MIN_SCALE)
+			* 1.15;
+
+		let size = frame.size();
+		let x_of = |sample: &TrafficSample| {
+			let age = now.duration_since(sample.at).as_secs_f32();
+			size.width * (1.0 - age / self.window.as_secs_f32())
+		};
+		let y_of = |value: u64| size.height * (1.0 - value as f32 / scale);
+
+		// Quarter lines give a sense of scale without labelling the axis
+		for step in 1..4 {
+			let y = size.height * step as f32 / 4.0;
+			frame.stroke(
+				&Path::line(Point::new(0.0, y), Point::new(size.width, y)),
+				Stroke::default()
+					.with_color(Color { a: 0.12, ..grid_color })
+					.with_width(1.0),
+			);
+		}
+
+		if samples.len() >= 2 {
+			let series: [(fn(&TrafficSample) -> u64, Color); 2] =
+				[(|s| s.down, down_color), (|s| s.up, up_color)];
+			for (value_of, color) in series {
+				let line = Path::new(|builder| {
+					for (i, sample) in samples.iter().enumerate() {
+						let point = Point::new(x_of(sample), y_of(value_of(sample)));
+						if i == 0 {
+							builder.move_to(point);
+						} else {
+							builder.line_to(point);
+						}
+					}
+				});
+				let area = Path::new(|builder| {
+					builder.move_to(Point::new(x_of(samples[0]), size.height));
+					for sample in &samples {
+						builder.line_to(Point::new(x_of(sample), y_of(value_of(sample))));
+					}
+					builder.line_to(Point::new(x_of(samples[samples.len() - 1]), size.height));
+					builder.close();
+				});
+				frame.fill(&area, Color { a: 0.15, ..color });
+				frame.stroke(&line, Stroke::default().with_color(color).with_width(2.0));
+			}
+		}
+
+		if let Some(peak) = peak.filter(|sample| sample.up.max(sample.down) > 0) {
+			let (value, color) = if peak.down >= peak.up {
+				(peak.down, down_color)
+			} else {
+				(peak.up, up_color)
+			};
+			let point = Point::new(x_of(peak), y_of(value));
+			frame.fill(&Path::circle(point, 4.0), color);
+
+			// Keep the label inside the frame near the right edge
+			let label_x = point.x.min(size.width - 110.0).max(0.0);
+			frame.fill_text(Text {
+				content: (self.peak_label)(value),
+				position: Point::new(label_x, (point.y - 18.0).max(0.0)),
+				color,
+				size: Pixels(12.0),
+				..Text::default()
+			});
+		}
+
+		vec![frame.into_geometry()]
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn record_drops_samples_older_than_the_span() {
+		let start = Instant::now();
+		let sample = |minutes: u64| TrafficSample {
+			at: start + Duration::from_secs(minutes * 60),
+			up: minutes,
+			down: minutes,
+		};
+		let minutes = |history: &VecDeque<TrafficSample>| -> Vec<u64> { history.iter().map(|s| s.up).collect() };
+
+		// A sample exactly one span old is still kept
+		let mut history = VecDeque::new();
+		for m in [0, 30, 60] {
+			record(&mut history, sample(m));
+		}
+		assert_eq!(minutes(&history), [0, 30, 60]);
+
+		record(&mut history, sample(61));
+		assert_eq!(minutes(&history), [30, 60, 61]);
+
+		record(&mut history, sample(120));
+		assert_eq!(minutes(&history), [60, 61, 120]);
+	}
+}